@@ -0,0 +1,177 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Compression for streamed output, which adds its extension to file names
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    /// The compression a file was written with, going by its extension
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// A buffered output file, compressed or not. Flushing compresses everything written so
+/// far and pushes it to disk, so if the run is killed the file is a truncated stream that
+/// still decompresses up to the last flush. `finish` ends the stream properly.
+pub enum OutputFile {
+    Plain(BufWriter<File>),
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl OutputFile {
+    pub fn new(file: File, compression: Compression) -> Result<Self> {
+        let file = BufWriter::new(file);
+        Ok(match compression {
+            Compression::None => OutputFile::Plain(file),
+            Compression::Gzip => OutputFile::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            )),
+            Compression::Zstd => OutputFile::Zstd(zstd::Encoder::new(file, 0)?),
+        })
+    }
+
+    /// Writes the end of the compressed stream. Nothing more should be written after.
+    pub fn finish(&mut self) -> Result<()> {
+        match self {
+            OutputFile::Plain(writer) => writer.flush()?,
+            OutputFile::Gzip(encoder) => {
+                encoder.try_finish()?;
+                encoder.get_mut().flush()?;
+            }
+            OutputFile::Zstd(encoder) => {
+                encoder.do_finish()?;
+                encoder.get_mut().flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            OutputFile::Plain(writer) => writer.write(buf),
+            OutputFile::Gzip(encoder) => encoder.write(buf),
+            OutputFile::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            OutputFile::Plain(writer) => writer.flush(),
+            OutputFile::Gzip(encoder) => encoder.flush(),
+            OutputFile::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// A CSV writer on an `OutputFile`, which can end the file's stream without giving up the
+/// writer. It can't be written to after that, but flushing it does nothing.
+pub struct CsvWriter(Option<ixa::csv::Writer<OutputFile>>);
+
+impl CsvWriter {
+    pub fn new(file: OutputFile) -> Self {
+        Self(Some(ixa::csv::Writer::from_writer(file)))
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(writer) = &mut self.0 {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.0.take() {
+            writer.into_inner().map_err(|e| e.into_error())?.finish()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl std::ops::Deref for CsvWriter {
+    type Target = ixa::csv::Writer<OutputFile>;
+    fn deref(&self) -> &Self::Target {
+        self.0
+            .as_ref()
+            .expect("CSV output written after it was finished")
+    }
+}
+
+impl std::ops::DerefMut for CsvWriter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+            .as_mut()
+            .expect("CSV output written after it was finished")
+    }
+}
+
+/// Opens a file written as an `OutputFile`, decompressing it according to its extension
+pub fn open(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    Ok(match Compression::from_path(path) {
+        Compression::None => Box::new(BufReader::new(file)),
+        Compression::Gzip => Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(
+            BufReader::new(file),
+        ))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_round_trip_and_truncated_streams() {
+        let dir = std::env::temp_dir().join(format!("compression_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let path = dir.join(format!("out.csv{}", compression.extension()));
+            let mut file = OutputFile::new(File::create(&path).unwrap(), compression).unwrap();
+            writeln!(file, "t,incidence").unwrap();
+            file.finish().unwrap();
+            let mut contents = String::new();
+            open(&path).unwrap().read_to_string(&mut contents).unwrap();
+            assert_eq!(contents, "t,incidence\n");
+
+            // Flushed but never finished, as when a run is killed
+            let mut file = OutputFile::new(File::create(&path).unwrap(), compression).unwrap();
+            writeln!(file, "0,1").unwrap();
+            file.flush().unwrap();
+            let mut contents = Vec::new();
+            let _ = open(&path).unwrap().read_to_end(&mut contents);
+            assert_eq!(contents, b"0,1\n");
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,423 @@
+use crate::compression::{CsvWriter, OutputFile};
+use crate::output_manager::OutputFiles;
+use crate::simulation_event::{ColumnType, ColumnValue, EventType, SimulationEvent};
+use anyhow::Result;
+use arrow_array::builder::{Float64Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+
+/// How events are written. JSON lines keep every event type in one file if wanted; the
+/// columnar formats always write a file per type, with a column for each field.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFormat {
+    Jsonl,
+    Csv,
+    /// Arrow IPC streams, which load straight into pandas, polars, R's arrow package, etc.
+    Arrow,
+}
+
+impl EventFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            EventFormat::Jsonl => "jsonl",
+            EventFormat::Csv => "csv",
+            EventFormat::Arrow => "arrow",
+        }
+    }
+}
+
+/// Which events are written, and how. Each setting left out of a parameter file keeps
+/// its value from the files it extends, or the default of writing every event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventOutputParams {
+    pub format: EventFormat,
+    /// The event types to write
+    pub types: Vec<EventType>,
+    /// The fraction of events of a type to write, for high-volume types like `contact`.
+    /// Types not listed are all written.
+    pub sample: BTreeMap<EventType, f64>,
+    /// Only events at or after `start` and before `end` are written
+    pub start: f64,
+    pub end: Option<f64>,
+    /// Write each type to its own file, e.g. `events_contact.jsonl`, instead of one
+    /// `events.jsonl`. Columnar formats are always split.
+    pub split_by_type: bool,
+}
+
+impl Default for EventOutputParams {
+    fn default() -> Self {
+        Self {
+            format: EventFormat::Jsonl,
+            types: EventType::ALL.to_vec(),
+            sample: BTreeMap::new(),
+            start: 0.0,
+            end: None,
+            split_by_type: false,
+        }
+    }
+}
+
+/// `[output.events]` as read from one parameter file or override, with only the
+/// settings it gives. Layers are merged setting by setting, so a scenario that sets
+/// `types` keeps the `format` from the file it extends.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EventOutputLayer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<EventFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<Vec<EventType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample: Option<BTreeMap<EventType, f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    split_by_type: Option<bool>,
+}
+
+impl EventOutputLayer {
+    /// Fills in any settings missing from `self` with those from `other`
+    pub fn extend_from(self, other: Self) -> Self {
+        Self {
+            format: self.format.or(other.format),
+            types: self.types.or(other.types),
+            sample: self.sample.or(other.sample),
+            start: self.start.or(other.start),
+            end: self.end.or(other.end),
+            split_by_type: self.split_by_type.or(other.split_by_type),
+        }
+    }
+}
+
+impl From<EventOutputParams> for EventOutputLayer {
+    fn from(params: EventOutputParams) -> Self {
+        Self {
+            format: Some(params.format),
+            types: Some(params.types),
+            sample: Some(params.sample),
+            start: Some(params.start),
+            end: params.end,
+            split_by_type: Some(params.split_by_type),
+        }
+    }
+}
+
+impl From<EventOutputLayer> for EventOutputParams {
+    fn from(layer: EventOutputLayer) -> Self {
+        let default = Self::default();
+        Self {
+            format: layer.format.unwrap_or(default.format),
+            types: layer.types.unwrap_or(default.types),
+            sample: layer.sample.unwrap_or(default.sample),
+            start: layer.start.unwrap_or(default.start),
+            end: layer.end,
+            split_by_type: layer.split_by_type.unwrap_or(default.split_by_type),
+        }
+    }
+}
+
+impl EventOutputParams {
+    pub fn validate(&self) -> Result<()> {
+        if let Some((event_type, p)) = self.sample.iter().find(|(_, p)| !(0.0..=1.0).contains(*p)) {
+            anyhow::bail!(
+                "sample fraction for {} must be between 0 and 1, got {p}",
+                event_type.name()
+            );
+        }
+        if let Some(end) = self.end
+            && end < self.start
+        {
+            anyhow::bail!("end ({end}) must not be before start ({})", self.start);
+        }
+        Ok(())
+    }
+
+    /// Whether an event passes the type and time filters. Sampling is up to the caller,
+    /// which owns the random number generator.
+    pub fn includes(&self, event: &SimulationEvent) -> bool {
+        let t = event.time();
+        self.types.contains(&event.event_type())
+            && t >= self.start
+            && self.end.is_none_or(|end| t < end)
+    }
+
+    /// The fraction of events of this type to keep
+    pub fn sample_fraction(&self, event_type: EventType) -> f64 {
+        self.sample.get(&event_type).copied().unwrap_or(1.0)
+    }
+}
+
+/// Writes events to one JSON lines file, or to one file per type in any format
+pub enum EventWriters {
+    Combined(OutputFile),
+    ByType(BTreeMap<EventType, EventSink>),
+}
+
+impl EventWriters {
+    pub fn new(files: &mut OutputFiles, params: &EventOutputParams) -> Result<Self> {
+        if params.format == EventFormat::Jsonl && !params.split_by_type {
+            return Ok(EventWriters::Combined(files.create_stream("events.jsonl")?));
+        }
+        let mut writers = BTreeMap::new();
+        for event_type in &params.types {
+            let name = format!("events_{}.{}", event_type.name(), params.format.extension());
+            let sink = match params.format {
+                EventFormat::Jsonl => EventSink::Jsonl(files.create_stream(&name)?),
+                EventFormat::Csv => {
+                    let mut writer = files.create_csv(&name)?;
+                    writer.write_record(event_type.columns().iter().map(|(name, _)| name))?;
+                    EventSink::Csv(writer)
+                }
+                // Arrow IPC is already compact, and readers expect an uncompressed stream
+                EventFormat::Arrow => {
+                    EventSink::Arrow(ArrowEventWriter::new(files.create(&name)?, *event_type)?)
+                }
+            };
+            writers.insert(*event_type, sink);
+        }
+        Ok(EventWriters::ByType(writers))
+    }
+
+    pub fn write(&mut self, event: &SimulationEvent) -> Result<()> {
+        let sink = match self {
+            EventWriters::Combined(writer) => return write_json_line(writer, event),
+            EventWriters::ByType(writers) => match writers.get_mut(&event.event_type()) {
+                Some(sink) => sink,
+                None => return Ok(()),
+            },
+        };
+        match sink {
+            EventSink::Jsonl(writer) => write_json_line(writer, event)?,
+            EventSink::Csv(writer) => {
+                writer.write_record(event.values()?.iter().map(|v| v.to_string()))?
+            }
+            EventSink::Arrow(writer) => writer.write(event)?,
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self {
+            EventWriters::Combined(writer) => writer.flush()?,
+            EventWriters::ByType(writers) => {
+                for sink in writers.values_mut() {
+                    match sink {
+                        EventSink::Jsonl(writer) => writer.flush()?,
+                        EventSink::Csv(writer) => writer.flush()?,
+                        EventSink::Arrow(writer) => writer.flush()?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes everything and ends compressed streams and formats with a footer. Nothing
+    /// can be written after.
+    pub fn finish(&mut self) -> Result<()> {
+        self.flush()?;
+        match self {
+            EventWriters::Combined(writer) => writer.finish()?,
+            EventWriters::ByType(writers) => {
+                for sink in writers.values_mut() {
+                    match sink {
+                        EventSink::Jsonl(writer) => writer.finish()?,
+                        EventSink::Csv(writer) => writer.finish()?,
+                        EventSink::Arrow(writer) => writer.finish()?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The file events of one type are written to
+// There's one per event type at most, so their size doesn't matter
+#[allow(clippy::large_enum_variant)]
+pub enum EventSink {
+    Jsonl(OutputFile),
+    Csv(CsvWriter),
+    Arrow(ArrowEventWriter),
+}
+
+fn write_json_line(writer: &mut OutputFile, event: &SimulationEvent) -> Result<()> {
+    serde_json::to_writer(&mut *writer, event)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+// Rows buffered per record batch; small enough to bound memory, large enough that the
+// per-batch overhead doesn't matter
+const ARROW_BATCH_ROWS: usize = 65_536;
+
+enum ColumnBuilder {
+    Float64(Float64Builder),
+    UInt64(UInt64Builder),
+}
+
+/// Writes events of one type as an Arrow IPC stream, buffering rows into record batches.
+/// A stream has no footer, so every batch written before a run is killed can still be read.
+pub struct ArrowEventWriter {
+    writer: StreamWriter<BufWriter<File>>,
+    schema: SchemaRef,
+    columns: Vec<ColumnBuilder>,
+    rows: usize,
+}
+
+impl ArrowEventWriter {
+    pub fn new(file: File, event_type: EventType) -> Result<Self> {
+        let fields: Vec<Field> = event_type
+            .columns()
+            .iter()
+            .map(|(name, column_type)| {
+                let data_type = match column_type {
+                    ColumnType::Float64 => DataType::Float64,
+                    ColumnType::UInt64 => DataType::UInt64,
+                };
+                Field::new(*name, data_type, false)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let columns = event_type
+            .columns()
+            .iter()
+            .map(|(_, column_type)| match column_type {
+                ColumnType::Float64 => ColumnBuilder::Float64(Float64Builder::new()),
+                ColumnType::UInt64 => ColumnBuilder::UInt64(UInt64Builder::new()),
+            })
+            .collect();
+        Ok(Self {
+            writer: StreamWriter::try_new_buffered(file, &schema)?,
+            schema,
+            columns,
+            rows: 0,
+        })
+    }
+
+    pub fn write(&mut self, event: &SimulationEvent) -> Result<()> {
+        // Every value is read before any is appended, so a failure can't leave a row half written
+        let values = event.values()?;
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            match (column, value) {
+                (ColumnBuilder::Float64(builder), ColumnValue::Float64(x)) => {
+                    builder.append_value(x)
+                }
+                (ColumnBuilder::UInt64(builder), ColumnValue::UInt64(x)) => builder.append_value(x),
+                _ => anyhow::bail!("{:?} doesn't match its schema", event),
+            }
+        }
+        self.rows += 1;
+        if self.rows == ARROW_BATCH_ROWS {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn write_batch(&mut self) -> Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let arrays: Vec<ArrayRef> = self
+            .columns
+            .iter_mut()
+            .map(|column| -> ArrayRef {
+                match column {
+                    ColumnBuilder::Float64(builder) => Arc::new(builder.finish()),
+                    ColumnBuilder::UInt64(builder) => Arc::new(builder.finish()),
+                }
+            })
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// Writes any buffered rows as a (possibly short) batch, so they're readable even if
+    /// the run doesn't finish
+    pub fn flush(&mut self) -> Result<()> {
+        self.write_batch()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        self.write_batch()?;
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ixa::prelude::*;
+
+    #[test]
+    fn test_partial_params_keep_defaults() {
+        let params: EventOutputParams = toml::from_str(
+            r#"
+            types = ["infection", "contact"]
+            sample = { contact = 0.01 }
+            end = 50.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(params.types, [EventType::Infection, EventType::Contact]);
+        assert_eq!(params.sample_fraction(EventType::Contact), 0.01);
+        assert_eq!(params.sample_fraction(EventType::Infection), 1.0);
+        assert_eq!(params.start, 0.0);
+        assert!(!params.split_by_type);
+        assert!(params.validate().is_ok());
+
+        let bad: EventOutputParams = toml::from_str("start = 10.0\nend = 5.0").unwrap();
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_arrow_columns_follow_the_event() {
+        let path = std::env::temp_dir().join(format!("events_{}.arrow", std::process::id()));
+        let mut writer =
+            ArrowEventWriter::new(File::create(&path).unwrap(), EventType::Contact).unwrap();
+        let mut context = Context::new();
+        let person_id = context.add_person(()).unwrap();
+        let contact_id = context.add_person(()).unwrap();
+        writer
+            .write(&SimulationEvent::Contact {
+                t: 1.5,
+                person_id,
+                contact_id,
+            })
+            .unwrap();
+        writer.finish().unwrap();
+
+        let reader =
+            arrow_ipc::reader::StreamReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let names: Vec<_> = reader
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        assert_eq!(names, ["t", "person_id", "contact_id"]);
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let contacts = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<arrow_array::UInt64Array>()
+            .unwrap();
+        assert_eq!(contacts.value(0), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::compression::CsvWriter;
+use crate::output_manager::OutputFiles;
+use anyhow::Result;
+use ixa::PersonId;
+
+const TRANSMISSIONS_FILE: &str = "generation_intervals.csv";
+const BY_DAY_FILE: &str = "generation_intervals_by_day.csv";
+
+/// Realized generation intervals: the time from an infector's infection to each of their
+/// infectees'. People infected at the start of the simulation count as infected at time 0.
+/// Serial intervals would need each person's symptom onset, which the model doesn't have.
+pub struct GenerationIntervalWriter {
+    transmissions: CsvWriter,
+    by_day_writer: CsvWriter,
+    // Intervals by the calendar day the infectee was infected
+    by_day: Vec<Vec<f64>>,
+}
+
+impl GenerationIntervalWriter {
+    pub fn new(files: &mut OutputFiles) -> Result<Self> {
+        let mut transmissions = files.create_csv(TRANSMISSIONS_FILE)?;
+        transmissions.write_record(["infector", "infectee", "t", "generation_interval"])?;
+        Ok(Self {
+            transmissions,
+            by_day_writer: files.create_csv(BY_DAY_FILE)?,
+            by_day: Vec::new(),
+        })
+    }
+
+    pub fn add_transmission(
+        &mut self,
+        infector: PersonId,
+        infectee: PersonId,
+        infection_time: f64,
+        infector_infection_time: f64,
+    ) -> Result<()> {
+        let interval = infection_time - infector_infection_time;
+        self.transmissions.write_record([
+            infector.to_string(),
+            infectee.to_string(),
+            infection_time.to_string(),
+            interval.to_string(),
+        ])?;
+        let day = infection_time.floor() as usize;
+        if day >= self.by_day.len() {
+            self.by_day.resize_with(day + 1, Vec::new);
+        }
+        self.by_day[day].push(interval);
+        Ok(())
+    }
+
+    /// The mean over every transmission so far, if there have been any
+    pub fn mean(&self) -> Option<f64> {
+        let (total, n) = self
+            .by_day
+            .iter()
+            .flatten()
+            .fold((0.0, 0), |(total, n), x| (total + x, n + 1));
+        (n > 0).then(|| total / n as f64)
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.transmissions.flush()?;
+        Ok(())
+    }
+
+    /// Writes the distribution of intervals for infections on each calendar day, so changes
+    /// over the course of the epidemic (e.g. contraction as susceptibles run out) show up
+    pub fn finish(&mut self) -> Result<()> {
+        self.transmissions.flush()?;
+        self.transmissions.finish()?;
+        let writer = &mut self.by_day_writer;
+        writer.write_record(["t", "transmissions", "mean", "q25", "median", "q75"])?;
+        for (day, intervals) in self.by_day.iter_mut().enumerate() {
+            if intervals.is_empty() {
+                writer.write_record([day.to_string().as_str(), "0", "", "", "", ""])?;
+                continue;
+            }
+            intervals.sort_by(f64::total_cmp);
+            let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+            writer.write_record(&[
+                day.to_string(),
+                intervals.len().to_string(),
+                mean.to_string(),
+                quantile(intervals, 0.25).to_string(),
+                quantile(intervals, 0.5).to_string(),
+                quantile(intervals, 0.75).to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+// Linearly interpolates between the closest ranks of sorted, non-empty values
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_quantile_interpolates() {
+        let sorted = [1.0, 2.0, 4.0, 8.0];
+        assert_eq!(quantile(&sorted, 0.0), 1.0);
+        assert_eq!(quantile(&sorted, 0.5), 3.0);
+        assert_eq!(quantile(&sorted, 1.0), 8.0);
+        assert_eq!(quantile(&[5.0], 0.25), 5.0);
+    }
+}
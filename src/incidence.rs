@@ -0,0 +1,171 @@
+use crate::compression::CsvWriter;
+use anyhow::Result;
+use ixa::{Context, PersonId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Ways daily incidence can be split, each adding a column to `daily_incidence.csv`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidenceStratum {
+    /// How many transmissions separate the person from an initial infection
+    Generation,
+}
+
+impl IncidenceStratum {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IncidenceStratum::Generation => "generation",
+        }
+    }
+}
+
+/// Gives the stratum an infected person falls in, e.g. their age group
+pub type StratumFn = Box<dyn Fn(&Context, PersonId) -> String>;
+
+/// Writes new infections per calendar day as each day closes rather than at the end of the
+/// run. Without strata there is a row for every day up to the last with an infection, zeros
+/// included, just as if the whole table were written at the end. With strata, a day has a
+/// row for each stratum with infections that day.
+pub struct DailyIncidenceWriter {
+    writer: CsvWriter,
+    strata: Vec<(String, StratumFn)>,
+    header_written: bool,
+    // Infections by stratum for each day not yet written
+    open_days: BTreeMap<usize, BTreeMap<Vec<String>, usize>>,
+    // The first day not yet closed
+    next_day: usize,
+    // Closed days with no infections, which are only written if a later day has some
+    empty_days: Vec<usize>,
+}
+
+impl DailyIncidenceWriter {
+    pub fn new(writer: CsvWriter) -> Self {
+        Self {
+            writer,
+            strata: Vec::new(),
+            header_written: false,
+            open_days: BTreeMap::new(),
+            next_day: 0,
+            empty_days: Vec::new(),
+        }
+    }
+
+    /// Adds a column splitting incidence by `stratum`. This has to happen before any rows
+    /// are written.
+    pub fn add_stratum(&mut self, name: &str, stratum: StratumFn) -> Result<()> {
+        if self.header_written {
+            anyhow::bail!("Can't stratify daily incidence by {name} once it's being written");
+        }
+        self.strata.push((name.to_string(), stratum));
+        Ok(())
+    }
+
+    /// The stratum values for an infected person, in column order
+    pub fn stratum(&self, context: &Context, person_id: PersonId) -> Vec<String> {
+        self.strata
+            .iter()
+            .map(|(_, stratum)| stratum(context, person_id))
+            .collect()
+    }
+
+    pub fn add_infection(&mut self, t: f64, stratum: Vec<String>) -> Result<()> {
+        let day = t.floor() as usize;
+        if day < self.next_day {
+            anyhow::bail!("Infection at {t} is on day {day}, which was already written");
+        }
+        *self
+            .open_days
+            .entry(day)
+            .or_default()
+            .entry(stratum)
+            .or_default() += 1;
+        Ok(())
+    }
+
+    /// Writes every day before `day`
+    pub fn close_days_before(&mut self, day: usize) -> Result<()> {
+        if !self.header_written {
+            let names = self.strata.iter().map(|(name, _)| name.as_str());
+            let header: Vec<&str> = std::iter::once("t")
+                .chain(names)
+                .chain(std::iter::once("incidence"))
+                .collect();
+            self.writer.write_record(&header)?;
+            self.header_written = true;
+        }
+        while self.next_day < day {
+            let closing = self.next_day;
+            self.next_day += 1;
+            let Some(counts) = self.open_days.remove(&closing) else {
+                self.empty_days.push(closing);
+                continue;
+            };
+            if self.strata.is_empty() {
+                for empty in self.empty_days.iter() {
+                    self.writer
+                        .write_record([empty.to_string(), "0".to_string()])?;
+                }
+            }
+            self.empty_days.clear();
+            for (stratum, incidence) in counts {
+                let record = std::iter::once(closing.to_string())
+                    .chain(stratum)
+                    .chain(std::iter::once(incidence.to_string()));
+                self.writer.write_record(record)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the days still open and ends the file. Only the first call does anything.
+    pub fn finish(&mut self) -> Result<()> {
+        if self.writer.is_finished() {
+            return Ok(());
+        }
+        let end = self
+            .open_days
+            .keys()
+            .last()
+            .map_or(self.next_day, |day| day + 1);
+        self.close_days_before(end)?;
+        self.writer.flush()?;
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compression::{Compression, OutputFile};
+    use std::fs::File;
+
+    #[test]
+    fn test_incremental_rows_match_the_whole_table() {
+        let path = std::env::temp_dir().join(format!("incidence_{}.csv", std::process::id()));
+        let file = OutputFile::new(File::create(&path).unwrap(), Compression::None).unwrap();
+        let mut writer = DailyIncidenceWriter::new(CsvWriter::new(file));
+        writer.add_infection(0.0, vec![]).unwrap();
+        writer.add_infection(0.5, vec![]).unwrap();
+        writer.close_days_before(1).unwrap();
+        writer.add_infection(2.25, vec![]).unwrap();
+        writer.close_days_before(4).unwrap();
+        writer.add_infection(5.0, vec![]).unwrap();
+        assert!(writer.add_infection(3.5, vec![]).is_err());
+        writer.close_days_before(7).unwrap();
+        // The end of a run finishes the writer and may then flush or finish it again
+        writer.finish().unwrap();
+        writer.flush().unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "t,incidence\n0,2\n1,0\n2,1\n3,0\n4,0\n5,1\n"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
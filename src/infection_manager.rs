@@ -1,5 +1,6 @@
 use crate::ext::*;
 use crate::infection_status::*;
+use crate::ixa_plus::distr::{copula::GaussianCopula, gamma::Gamma};
 use crate::ixa_plus::rate_fn::*;
 use crate::simulation_event::SimulationEvent;
 use crate::total_infectiousness_multiplier;
@@ -9,13 +10,39 @@ use rand_distr::Exp;
 
 define_rng!(InfectionRng);
 define_rng!(ForecastRng);
+define_rng!(ImportRng);
 
 define_rate!(InfectionRate, |context, _person_id| {
-    let r_distr = context.param_infection_rate();
-    let duration_distr = context.param_infection_duration();
+    let r_distr = context.param_transmission_infection_rate();
+    let duration_distr = context.param_transmission_infection_duration();
+    let correlation = *context.param_transmission_rate_duration_correlation();
+    let (r, infection_duration) = if let Some(offspring) = context.param_transmission_offspring() {
+        // Draw the person's reproduction number and spread it evenly over their infection,
+        // so that the rate function integrates to exactly that number
+        // A gamma with shape k has a coefficient of variation of 1 / sqrt(k)
+        let individual_r = Gamma::from_mean_cv(offspring.r0, offspring.k.powf(-0.5)).unwrap();
+        let individual_r = context.sample_distr(InfectionRng, individual_r);
+        let infection_duration = context.sample_distr(InfectionRng, duration_distr);
+        let r = if infection_duration > 0.0 {
+            individual_r / infection_duration
+        } else {
+            0.0
+        };
+        (r, infection_duration)
+    } else if correlation == 0.0 {
+        (
+            context.sample_distr(InfectionRng, r_distr),
+            context.sample_distr(InfectionRng, duration_distr),
+        )
+    } else {
+        let copula = GaussianCopula::new(correlation).unwrap();
+        context.sample(InfectionRng, |rng| {
+            copula.sample_pair(rng, r_distr, duration_distr)
+        })
+    };
     let params = ConstantRateParams {
-        r: context.sample_distr(InfectionRng, r_distr),
-        infection_duration: context.sample_distr(InfectionRng, duration_distr),
+        r,
+        infection_duration,
     };
     log::trace!("Assigning infection rate: {params:?}");
     RateFn::ConstantRate(params.try_into().unwrap())
@@ -143,6 +170,30 @@ pub trait InfectionManagerExt: PluginContext {
         self.schedule_recovery(person_id);
     }
 
+    /// Each day, draws the number of imported cases from `population.imported_cases` and
+    /// infects that many susceptible people, stopping early if none are left
+    fn schedule_imported_cases(&mut self) {
+        let Some(imported_cases) = self.param_population_imported_cases().clone() else {
+            return;
+        };
+        self.add_periodic_plan_with_phase(
+            1.0,
+            move |context| {
+                let count = context.sample_distr(ImportRng, &imported_cases);
+                for _ in 0..count {
+                    let Some(person_id) = context
+                        .sample_person(ImportRng, (InfectionCompartment, Compartment::Susceptible))
+                    else {
+                        break;
+                    };
+                    // No infector: the case was infected outside the population
+                    context.infect_person(person_id, None, Some(context.get_current_time()));
+                }
+            },
+            ExecutionPhase::Normal,
+        );
+    }
+
     /// Assigns a person's status to recovered. If the person was recovered, there
     /// will be no associated metadata about the infection and recovery time
     fn recover_person(&mut self, person_id: PersonId, recovery_time: Option<f64>) -> Result<()> {
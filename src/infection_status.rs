@@ -41,6 +41,12 @@ impl Status {
             Status::Susceptible => None,
         }
     }
+    pub fn recovery_time(&self) -> Option<f64> {
+        match self {
+            Status::Recovered(InfectionData { recovery_time, .. }) => *recovery_time,
+            _ => None,
+        }
+    }
     pub fn is_infectious(&self) -> bool {
         matches!(self, Status::Infectious { .. })
     }
@@ -63,3 +69,38 @@ impl Status {
         }
     }
 }
+
+/// Which compartment a person is in, without the infection details of `Status`, so people
+/// can be indexed and counted by it
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Compartment {
+    Susceptible,
+    Infectious,
+    Recovered,
+}
+
+impl Compartment {
+    pub const ALL: [Compartment; 3] = [
+        Compartment::Susceptible,
+        Compartment::Infectious,
+        Compartment::Recovered,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compartment::Susceptible => "S",
+            Compartment::Infectious => "I",
+            Compartment::Recovered => "R",
+        }
+    }
+}
+
+define_derived_property!(
+    InfectionCompartment,
+    Compartment,
+    [InfectionStatus],
+    |status| match status {
+        Status::Susceptible => Compartment::Susceptible,
+        Status::Infectious(_) => Compartment::Infectious,
+        Status::Recovered(_) => Compartment::Recovered,
+    }
+);
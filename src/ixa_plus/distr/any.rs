@@ -0,0 +1,254 @@
+pub use super::distribution::{ContinuousUnivariate, DiscreteUnivariate, DistrError, Distribution};
+use super::{
+    beta::Beta, categorical::Categorical, constant::Constant, exponential::Exponential,
+    gamma::Gamma, geometric::Geometric, lognormal::LogNormal, mixture::Mixture,
+    negative_binomial::NegativeBinomial, normal::Normal, poisson::Poisson, shifted::Shifted,
+    truncated::Truncated, uniform::Uniform, weibull::Weibull,
+};
+use serde::{Deserialize, Serialize};
+
+// Distributions that can be picked by name in a parameters file
+macro_rules! any_distribution_enum {
+    ($($name:ident($ty:ty) => $tag:literal),*) => {
+        /// A continuous distribution whose family is chosen in the parameters file
+        /// with a `type` key, e.g. `{ type = "gamma", shape = 2.0, rate = 0.25 }`
+        #[derive(Serialize, Deserialize, Debug, Clone)]
+        #[serde(tag = "type")]
+        pub enum AnyDistribution {
+            $(
+                #[serde(rename = $tag)]
+                $name($ty),
+            )*
+        }
+
+        impl ContinuousUnivariate<f64, f64> for AnyDistribution {
+            fn pdf(&self, x: f64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.pdf(x),)*
+                }
+            }
+            fn ln_pdf(&self, x: f64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.ln_pdf(x),)*
+                }
+            }
+            fn cdf(&self, x: f64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.cdf(x),)*
+                }
+            }
+            fn inverse_cdf(&self, p: f64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.inverse_cdf(p),)*
+                }
+            }
+            fn mean(&self) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.mean(),)*
+                }
+            }
+            fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.partial_expectation(lower, upper),)*
+                }
+            }
+        }
+
+        impl Distribution<f64> for AnyDistribution {
+            fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+                match self {
+                    $(Self::$name(d) => Distribution::sample(d, rng),)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for AnyDistribution {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    $(Self::$name(d) => d.fmt(f),)*
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for AnyDistribution {
+                fn from(d: $ty) -> Self {
+                    Self::$name(d)
+                }
+            }
+        )*
+    };
+}
+
+any_distribution_enum!(
+    Gamma(Gamma) => "gamma",
+    LogNormal(LogNormal) => "lognormal",
+    Weibull(Weibull) => "weibull",
+    Exponential(Exponential) => "exponential",
+    Uniform(Uniform) => "uniform",
+    Normal(Normal) => "normal",
+    Beta(Beta) => "beta",
+    Constant(Constant) => "constant",
+    Truncated(Truncated<AnyDistribution>) => "truncated",
+    Shifted(Shifted<AnyDistribution>) => "shifted",
+    Mixture(Mixture<AnyDistribution>) => "mixture"
+);
+
+// Mirror trait methods for convenience
+impl AnyDistribution {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+// Count-valued distributions that can be picked by name in a parameters file
+macro_rules! any_discrete_distribution_enum {
+    ($($name:ident($ty:ty) => $tag:literal),*) => {
+        /// A discrete distribution whose family is chosen in the parameters file
+        /// with a `type` key, e.g. `{ type = "negative_binomial", mean = 2.5, k = 0.5 }`
+        #[derive(Serialize, Deserialize, Debug, Clone)]
+        #[serde(tag = "type")]
+        pub enum AnyDiscreteDistribution {
+            $(
+                #[serde(rename = $tag)]
+                $name($ty),
+            )*
+        }
+
+        impl DiscreteUnivariate<u64, f64> for AnyDiscreteDistribution {
+            fn pmf(&self, x: u64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.pmf(x),)*
+                }
+            }
+            fn ln_pmf(&self, x: u64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.ln_pmf(x),)*
+                }
+            }
+            fn cdf(&self, x: u64) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.cdf(x),)*
+                }
+            }
+            fn inverse_cdf(&self, p: f64) -> u64 {
+                match self {
+                    $(Self::$name(d) => d.inverse_cdf(p),)*
+                }
+            }
+            fn mean(&self) -> f64 {
+                match self {
+                    $(Self::$name(d) => d.mean(),)*
+                }
+            }
+        }
+
+        impl Distribution<u64> for AnyDiscreteDistribution {
+            fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+                match self {
+                    $(Self::$name(d) => Distribution::sample(d, rng),)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for AnyDiscreteDistribution {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    $(Self::$name(d) => d.fmt(f),)*
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for AnyDiscreteDistribution {
+                fn from(d: $ty) -> Self {
+                    Self::$name(d)
+                }
+            }
+        )*
+    };
+}
+
+any_discrete_distribution_enum!(
+    Poisson(Poisson) => "poisson",
+    NegativeBinomial(NegativeBinomial) => "negative_binomial",
+    Geometric(Geometric) => "geometric",
+    Categorical(Categorical) => "categorical"
+);
+
+// Mirror trait methods for convenience
+impl AnyDiscreteDistribution {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        <Self as Distribution<u64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as DiscreteUnivariate<u64, f64>>::mean(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_family_chosen_by_type() {
+        let d: AnyDistribution =
+            toml::from_str("type = \"lognormal\"\nmean = 5.2\nsd = 1.0").unwrap();
+        assert!(matches!(d, AnyDistribution::LogNormal(_)));
+        assert!((d.mean() - 5.2).abs() < 1e-12);
+        assert_eq!(d.to_string(), "LogNormal(mean = 5.2, sd = 1)");
+
+        let serialized = toml::to_string(&d).unwrap();
+        assert!(serialized.contains("type = \"lognormal\""), "{serialized}");
+    }
+
+    #[test]
+    fn test_unknown_type_is_an_error() {
+        let result: Result<AnyDistribution, _> = toml::from_str("type = \"cauchy\"\nx = 1.0");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_wrappers_nest_any_distribution() {
+        let d: AnyDistribution = toml::from_str(
+            r#"
+            type = "shifted"
+            shift = 1.0
+            [distribution]
+            type = "truncated"
+            upper = 10.0
+            [distribution.distribution]
+            type = "gamma"
+            shape = 3.0
+            rate = 1.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            d.to_string(),
+            "Shifted(Truncated(Gamma(shape = 3, rate = 1), upper = 10), shift = 1)"
+        );
+        assert!(d.cdf(1.0) == 0.0 && d.cdf(11.0) == 1.0);
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            assert!((1.0..=11.0).contains(&d.sample(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_discrete_family_chosen_by_type() {
+        let d: AnyDiscreteDistribution =
+            toml::from_str("type = \"categorical\"\nvalues = [1, 2]\nweights = [3.0, 1.0]")
+                .unwrap();
+        assert!(matches!(d, AnyDiscreteDistribution::Categorical(_)));
+        assert!((d.mean() - 1.25).abs() < 1e-12);
+        let mut rng = rand::rng();
+        assert!([1, 2].contains(&d.sample(&mut rng)));
+
+        let d: AnyDiscreteDistribution = toml::from_str("type = \"poisson\"\nmean = 4.0").unwrap();
+        assert_eq!(d.to_string(), "Poisson(mean = 4)");
+    }
+}
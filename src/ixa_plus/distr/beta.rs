@@ -0,0 +1,173 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+pub use crate::beta;
+use serde::{Deserialize, Serialize};
+use statrs::{
+    distribution::{self as sd, Continuous, ContinuousCDF},
+    statistics::Distribution as StatisticsDistribution,
+};
+
+#[macro_export]
+macro_rules! beta {
+    (alpha = $alpha:expr, beta = $beta:expr) => {
+        $crate::ixa_plus::distr::beta::BetaParams::Shape {
+            alpha: $alpha,
+            beta: $beta,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, sd = $sd:expr) => {
+        $crate::ixa_plus::distr::beta::BetaParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+        }
+        .try_into()
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum BetaParams {
+    Shape { alpha: f64, beta: f64 },
+    MeanSd { mean: f64, sd: f64 },
+}
+
+impl TryFrom<BetaParams> for sd::Beta {
+    type Error = DistrError;
+    fn try_from(params: BetaParams) -> Result<sd::Beta, DistrError> {
+        match params {
+            BetaParams::Shape { alpha, beta } => Ok(sd::Beta::new(alpha, beta)?),
+            BetaParams::MeanSd { mean, sd } => {
+                let variance = sd * sd;
+                if mean <= 0.0 || mean >= 1.0 || sd <= 0.0 || variance >= mean * (1.0 - mean) {
+                    return Err(DistrError(format!(
+                        "Beta mean must be in (0, 1) and sd^2 less than mean * (1 - mean); got mean {mean}, sd {sd}"
+                    )));
+                }
+                let concentration = mean * (1.0 - mean) / variance - 1.0;
+                Ok(sd::Beta::new(
+                    mean * concentration,
+                    (1.0 - mean) * concentration,
+                )?)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Beta {
+    params: BetaParams,
+    distr: sd::Beta,
+}
+
+impl Beta {
+    pub fn from_alpha_beta(alpha: f64, beta: f64) -> Result<Self, DistrError> {
+        Self::try_from(BetaParams::Shape { alpha, beta })
+    }
+    pub fn from_mean_sd(mean: f64, sd: f64) -> Result<Self, DistrError> {
+        Self::try_from(BetaParams::MeanSd { mean, sd })
+    }
+    pub fn alpha(&self) -> f64 {
+        self.distr.shape_a()
+    }
+    pub fn beta(&self) -> f64 {
+        self.distr.shape_b()
+    }
+}
+
+// Mirror trait methods for convenience
+impl Beta {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<BetaParams> for Beta {
+    type Error = DistrError;
+
+    fn try_from(params: BetaParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Beta {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            BetaParams::Shape { alpha, beta } => write!(f, "Beta(alpha = {alpha}, beta = {beta})"),
+            BetaParams::MeanSd { mean, sd } => write!(f, "Beta(mean = {mean}, sd = {sd})"),
+        }
+    }
+}
+
+impl Serialize for Beta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Beta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = BetaParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Beta {
+    fn pdf(&self, x: f64) -> f64 {
+        self.distr.pdf(x)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.distr.ln_pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        self.distr.mean().unwrap()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // x times the density is the mean times the density of a Beta(a + 1, b)
+        let size_biased = sd::Beta::new(self.distr.shape_a() + 1.0, self.distr.shape_b()).unwrap();
+        let cdf = |x: f64| size_biased.cdf(x.clamp(0.0, 1.0));
+        self.mean() * (cdf(upper) - cdf(lower))
+    }
+}
+
+impl Distribution<f64> for Beta {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mean_sd_matches_moments() {
+        let d: Beta = beta!(mean = 0.3, sd = 0.1).unwrap();
+        assert_relative_eq!(d.mean(), 0.3, epsilon = 1e-12);
+        assert_relative_eq!(d.distr.std_dev().unwrap(), 0.1, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_mean_sd_rejects_impossible_variance() {
+        let result: Result<Beta, _> = beta!(mean = 0.5, sd = 0.6);
+        assert!(result.is_err());
+    }
+}
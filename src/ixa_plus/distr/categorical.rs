@@ -0,0 +1,205 @@
+pub use super::distribution::{DiscreteUnivariate, DistrError, Distribution};
+use serde::{Deserialize, Serialize};
+
+/// A distribution over a finite set of counts, given either as `values` with matching
+/// (unnormalized) `weights`, or as a list of `observations` whose empirical frequencies
+/// are used, e.g. household sizes from a survey.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CategoricalParams {
+    Weights { values: Vec<u64>, weights: Vec<f64> },
+    Observations { observations: Vec<u64> },
+}
+
+#[derive(Debug, Clone)]
+pub struct Categorical {
+    params: CategoricalParams,
+    // Distinct values in increasing order, their probabilities, and the running total
+    values: Vec<u64>,
+    probabilities: Vec<f64>,
+    cumulative: Vec<f64>,
+}
+
+impl Categorical {
+    pub fn from_weights(values: Vec<u64>, weights: Vec<f64>) -> Result<Self, DistrError> {
+        Self::try_from(CategoricalParams::Weights { values, weights })
+    }
+    pub fn from_observations(observations: Vec<u64>) -> Result<Self, DistrError> {
+        Self::try_from(CategoricalParams::Observations { observations })
+    }
+    /// The values with non-zero probability, in increasing order
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+}
+
+// Mirror trait methods for convenience
+impl Categorical {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        <Self as Distribution<u64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as DiscreteUnivariate<u64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<CategoricalParams> for Categorical {
+    type Error = DistrError;
+
+    fn try_from(params: CategoricalParams) -> Result<Self, Self::Error> {
+        let mut pairs: Vec<(u64, f64)> = match &params {
+            CategoricalParams::Weights { values, weights } => {
+                if values.len() != weights.len() {
+                    return Err(DistrError(format!(
+                        "Categorical needs one weight per value, got {} values and {} weights",
+                        values.len(),
+                        weights.len()
+                    )));
+                }
+                if let Some(w) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+                    return Err(DistrError(format!(
+                        "Categorical weights must be finite and non-negative, got {w}"
+                    )));
+                }
+                values
+                    .iter()
+                    .copied()
+                    .zip(weights.iter().copied())
+                    .collect()
+            }
+            CategoricalParams::Observations { observations } => {
+                observations.iter().map(|&x| (x, 1.0)).collect()
+            }
+        };
+        pairs.sort_by_key(|(x, _)| *x);
+
+        let mut values: Vec<u64> = Vec::new();
+        let mut probabilities: Vec<f64> = Vec::new();
+        for (x, w) in pairs.into_iter().filter(|(_, w)| *w > 0.0) {
+            if values.last() == Some(&x) {
+                *probabilities.last_mut().unwrap() += w;
+            } else {
+                values.push(x);
+                probabilities.push(w);
+            }
+        }
+        let total: f64 = probabilities.iter().sum();
+        if values.is_empty() || total <= 0.0 {
+            return Err(DistrError(
+                "Categorical needs at least one value with positive weight".to_string(),
+            ));
+        }
+        for p in &mut probabilities {
+            *p /= total;
+        }
+        let cumulative = probabilities
+            .iter()
+            .scan(0.0, |acc, p| {
+                *acc += p;
+                Some(*acc)
+            })
+            .collect();
+
+        Ok(Self {
+            params,
+            values,
+            probabilities,
+            cumulative,
+        })
+    }
+}
+
+impl std::fmt::Display for Categorical {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let pmf = self
+            .values
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(x, p)| format!("{x}: {p:.4}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "Categorical({pmf})")
+    }
+}
+
+impl Serialize for Categorical {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Categorical {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = CategoricalParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl DiscreteUnivariate<u64, f64> for Categorical {
+    fn pmf(&self, x: u64) -> f64 {
+        match self.values.binary_search(&x) {
+            Ok(i) => self.probabilities[i],
+            Err(_) => 0.0,
+        }
+    }
+    fn ln_pmf(&self, x: u64) -> f64 {
+        self.pmf(x).ln()
+    }
+    fn cdf(&self, x: u64) -> f64 {
+        match self.values.partition_point(|v| *v <= x) {
+            0 => 0.0,
+            n => self.cumulative[n - 1].min(1.0),
+        }
+    }
+    fn inverse_cdf(&self, p: f64) -> u64 {
+        // The smallest value whose cdf reaches p; rounding can leave the last total just
+        // under 1, so fall back to the largest value
+        let i = self.cumulative.partition_point(|c| *c < p);
+        self.values[i.min(self.values.len() - 1)]
+    }
+    fn mean(&self) -> f64 {
+        self.values
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(x, p)| *x as f64 * p)
+            .sum()
+    }
+}
+
+impl Distribution<u64> for Categorical {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        self.inverse_cdf(rng.random::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_observations_give_empirical_pmf() {
+        let d = Categorical::from_observations(vec![3, 1, 2, 2, 4, 2, 1, 3]).unwrap();
+        assert_eq!(d.values(), &[1, 2, 3, 4]);
+        assert_relative_eq!(d.pmf(2), 3.0 / 8.0);
+        assert_relative_eq!(d.pmf(5), 0.0);
+        assert_relative_eq!(d.cdf(0), 0.0);
+        assert_relative_eq!(d.cdf(2), 5.0 / 8.0);
+        assert_relative_eq!(d.mean(), 18.0 / 8.0);
+        assert_eq!(d.inverse_cdf(0.5), 2);
+        assert_eq!(d.inverse_cdf(1.0), 4);
+    }
+
+    #[test]
+    fn test_weights_must_match_values() {
+        assert!(Categorical::from_weights(vec![1, 2], vec![1.0]).is_err());
+        assert!(Categorical::from_weights(vec![1, 2], vec![0.0, 0.0]).is_err());
+        assert!(Categorical::from_weights(vec![1, 2], vec![1.0, -1.0]).is_err());
+    }
+}
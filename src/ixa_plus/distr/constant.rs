@@ -0,0 +1,135 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+pub use crate::constant;
+use serde::{Deserialize, Serialize};
+
+#[macro_export]
+macro_rules! constant {
+    (value = $value:expr) => {
+        $crate::ixa_plus::distr::constant::ConstantParams { value: $value }.try_into()
+    };
+}
+
+/// A point mass: every sample is `value`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ConstantParams {
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Constant {
+    params: ConstantParams,
+}
+
+impl Constant {
+    pub fn new(value: f64) -> Result<Self, DistrError> {
+        Self::try_from(ConstantParams { value })
+    }
+    pub fn value(&self) -> f64 {
+        self.params.value
+    }
+}
+
+// Mirror trait methods for convenience
+impl Constant {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<ConstantParams> for Constant {
+    type Error = DistrError;
+
+    fn try_from(params: ConstantParams) -> Result<Self, Self::Error> {
+        if !params.value.is_finite() {
+            return Err(DistrError(format!(
+                "Constant value must be finite, got {}",
+                params.value
+            )));
+        }
+        Ok(Self { params })
+    }
+}
+
+impl std::fmt::Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Constant(value = {})", self.params.value)
+    }
+}
+
+impl Serialize for Constant {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Constant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = ConstantParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Constant {
+    // A point mass has no density. Returning 0 everywhere, rather than an infinite spike at
+    // `value`, keeps code that sums or integrates densities finite; the mass itself is
+    // carried by `cdf` and `inverse_cdf`, which is what sampling and truncation use.
+    fn pdf(&self, _x: f64) -> f64 {
+        0.0
+    }
+    fn ln_pdf(&self, _x: f64) -> f64 {
+        f64::NEG_INFINITY
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x < self.params.value { 0.0 } else { 1.0 }
+    }
+    fn inverse_cdf(&self, _p: f64) -> f64 {
+        self.params.value
+    }
+    fn mean(&self) -> f64 {
+        self.params.value
+    }
+    // Matches `cdf`, which puts the mass inside (lower, upper]
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        let value = self.params.value;
+        if lower < value && value <= upper {
+            value
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Distribution<f64> for Constant {
+    fn sample<R: rand::Rng + ?Sized>(&self, _rng: &mut R) -> f64 {
+        self.params.value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_point_mass() {
+        let d: Constant = constant!(value = 3.0).unwrap();
+        let mut rng = rand::rng();
+        assert_eq!(d.sample(&mut rng), 3.0);
+        assert_eq!(d.mean(), 3.0);
+        assert_eq!(d.cdf(2.999), 0.0);
+        assert_eq!(d.cdf(3.0), 1.0);
+        assert_eq!(d.inverse_cdf(0.2), 3.0);
+        assert_eq!(d.pdf(3.0), 0.0);
+
+        let result: Result<Constant, _> = constant!(value = f64::NAN);
+        assert!(result.is_err());
+    }
+}
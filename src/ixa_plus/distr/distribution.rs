@@ -4,4 +4,53 @@ pub trait ContinuousUnivariate<K, T> {
     fn ln_pdf(&self, x: K) -> T;
     fn cdf(&self, x: K) -> T;
     fn inverse_cdf(&self, p: T) -> K;
+    fn mean(&self) -> K;
+    /// The integral of `x * pdf(x)` from `lower` to `upper`. Divided by the mass between
+    /// them, this is the mean of the distribution truncated to those bounds.
+    fn partial_expectation(&self, lower: K, upper: K) -> K;
 }
+
+/// The counterpart of `ContinuousUnivariate` for count-valued distributions
+pub trait DiscreteUnivariate<K, T> {
+    fn pmf(&self, x: K) -> T;
+    fn ln_pmf(&self, x: K) -> T;
+    fn cdf(&self, x: K) -> T;
+    fn inverse_cdf(&self, p: T) -> K;
+    fn mean(&self) -> T;
+}
+
+/// Returned when a distribution is configured with invalid parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistrError(pub String);
+
+impl std::fmt::Display for DistrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DistrError {}
+
+macro_rules! impl_from_statrs_error {
+    ($($error:ident),*) => {
+        $(
+            impl From<statrs::distribution::$error> for DistrError {
+                fn from(e: statrs::distribution::$error) -> Self {
+                    DistrError(e.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_statrs_error!(
+    GammaError,
+    LogNormalError,
+    WeibullError,
+    ExpError,
+    UniformError,
+    NormalError,
+    BetaError,
+    PoissonError,
+    NegativeBinomialError
+);
@@ -0,0 +1,148 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+pub use crate::exponential;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{self as sd, Continuous, ContinuousCDF};
+
+#[macro_export]
+macro_rules! exponential {
+    (rate = $rate:expr) => {
+        $crate::ixa_plus::distr::exponential::ExponentialParams::Rate { rate: $rate }.try_into()
+    };
+    (mean = $mean:expr) => {
+        $crate::ixa_plus::distr::exponential::ExponentialParams::Mean { mean: $mean }.try_into()
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum ExponentialParams {
+    Rate { rate: f64 },
+    Mean { mean: f64 },
+}
+
+impl TryFrom<ExponentialParams> for sd::Exp {
+    type Error = DistrError;
+    fn try_from(params: ExponentialParams) -> Result<sd::Exp, DistrError> {
+        match params {
+            ExponentialParams::Rate { rate } => Ok(sd::Exp::new(rate)?),
+            ExponentialParams::Mean { mean } => Ok(sd::Exp::new(1.0 / mean)?),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Exponential {
+    params: ExponentialParams,
+    distr: sd::Exp,
+}
+
+impl Exponential {
+    pub fn from_rate(rate: f64) -> Result<Self, DistrError> {
+        Self::try_from(ExponentialParams::Rate { rate })
+    }
+    pub fn from_mean(mean: f64) -> Result<Self, DistrError> {
+        Self::try_from(ExponentialParams::Mean { mean })
+    }
+    pub fn rate(&self) -> f64 {
+        self.distr.rate()
+    }
+}
+
+// Mirror trait methods for convenience
+impl Exponential {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<ExponentialParams> for Exponential {
+    type Error = DistrError;
+
+    fn try_from(params: ExponentialParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Exponential {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            ExponentialParams::Rate { rate } => write!(f, "Exponential(rate = {rate})"),
+            ExponentialParams::Mean { mean } => write!(f, "Exponential(mean = {mean})"),
+        }
+    }
+}
+
+impl Serialize for Exponential {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Exponential {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = ExponentialParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Exponential {
+    fn pdf(&self, x: f64) -> f64 {
+        self.distr.pdf(x)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.distr.ln_pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        1.0 / self.distr.rate()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // x times the density is the mean times a Gamma(2, rate) density
+        let size_biased = sd::Gamma::new(2.0, self.distr.rate()).unwrap();
+        self.mean() * (size_biased.cdf(upper) - size_biased.cdf(lower))
+    }
+}
+
+impl Distribution<f64> for Exponential {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_rate_and_mean_agree() {
+        let by_rate: Exponential = exponential!(rate = 0.25).unwrap();
+        let by_mean: Exponential = exponential!(mean = 4.0).unwrap();
+        assert_relative_eq!(by_rate.mean(), 4.0);
+        assert_relative_eq!(by_mean.rate(), 0.25);
+        assert_relative_eq!(by_rate.cdf(4.0), 1.0 - (-1.0f64).exp(), epsilon = 1e-12);
+        assert_relative_eq!(by_mean.inverse_cdf(0.5), 4.0 * 2.0f64.ln(), epsilon = 1e-12);
+
+        let n = 100_000;
+        let mut rng = rand::rng();
+        let mean = (0..n).map(|_| by_mean.sample(&mut rng)).sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 4.0, epsilon = 0.1);
+    }
+}
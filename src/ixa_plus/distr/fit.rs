@@ -0,0 +1,140 @@
+use super::distribution::DistrError;
+use serde::{Deserialize, Serialize};
+
+/// A point on a distribution's cdf: a fraction `p` of the mass lies at or below `x`.
+/// In a parameters file, e.g. `quantiles = [{ p = 0.025, x = 4.1 }, { p = 0.975, x = 7.0 }]`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Quantile {
+    pub p: f64,
+    pub x: f64,
+}
+
+// With three quantiles the fit is least squares, so it can't match them all exactly.
+// Each fitted quantile must land within this fraction of the spread of the given ones.
+const QUANTILE_TOLERANCE: f64 = 0.05;
+
+/// Checks that `quantiles` can be fit: two or three of them, with `p` in (0, 1) and
+/// `x` strictly increasing with `p` (and positive for families on (0, inf)).
+pub fn check_quantiles(
+    family: &str,
+    quantiles: &[Quantile],
+    positive: bool,
+) -> Result<(), DistrError> {
+    if !(2..=3).contains(&quantiles.len()) {
+        return Err(DistrError(format!(
+            "{family} needs two or three quantiles to fit, got {}",
+            quantiles.len()
+        )));
+    }
+    for &Quantile { p, x } in quantiles {
+        if !(p > 0.0 && p < 1.0) {
+            return Err(DistrError(format!(
+                "{family} quantile p = {p} must be between 0 and 1"
+            )));
+        }
+        if !x.is_finite() || (positive && x <= 0.0) {
+            return Err(DistrError(format!(
+                "{family} quantile x = {x} must be {}",
+                if positive { "greater than 0" } else { "finite" }
+            )));
+        }
+    }
+    for pair in quantiles.windows(2) {
+        if pair[0].p >= pair[1].p {
+            return Err(DistrError(format!(
+                "{family} quantiles must be listed in increasing order of p"
+            )));
+        }
+        if pair[0].x >= pair[1].x {
+            return Err(DistrError(format!(
+                "{family} quantiles are inconsistent: the {} quantile ({}) must be less than \
+                 the {} quantile ({})",
+                pair[0].p, pair[0].x, pair[1].p, pair[1].x
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a fitted distribution's `inverse_cdf` reproduces `quantiles`
+pub fn check_fit(
+    family: &str,
+    quantiles: &[Quantile],
+    inverse_cdf: impl Fn(f64) -> f64,
+) -> Result<(), DistrError> {
+    let spread = quantiles[quantiles.len() - 1].x - quantiles[0].x;
+    for &Quantile { p, x } in quantiles {
+        let fitted = inverse_cdf(p);
+        if !fitted.is_finite() || (fitted - x).abs() > QUANTILE_TOLERANCE * spread {
+            return Err(DistrError(format!(
+                "{family} quantiles are inconsistent: the closest fit puts the {p} quantile \
+                 at {fitted:.4} rather than {x}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Least squares fit of `y = intercept + slope * z`, returning `(intercept, slope)`
+pub fn fit_line(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let z_mean = points.iter().map(|(z, _)| z).sum::<f64>() / n;
+    let y_mean = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let cov: f64 = points
+        .iter()
+        .map(|(z, y)| (z - z_mean) * (y - y_mean))
+        .sum();
+    let var: f64 = points.iter().map(|(z, _)| (z - z_mean).powi(2)).sum();
+    let slope = cov / var;
+    (y_mean - slope * z_mean, slope)
+}
+
+/// Finds where an increasing function `f` crosses zero in `[lo, hi]` by bisection
+pub fn bisect(f: impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> Option<f64> {
+    if f(lo) > 0.0 || f(hi) < 0.0 {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        if f(mid) < 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some((lo + hi) / 2.0)
+}
+
+/// Minimizes `f` over `[lo, hi]`: a coarse grid search to find the right basin, then
+/// golden section search within it.
+pub fn minimize(f: impl Fn(f64) -> f64, lo: f64, hi: f64) -> f64 {
+    const GRID: usize = 100;
+    let step = (hi - lo) / GRID as f64;
+    let best = (0..=GRID)
+        .map(|i| lo + step * i as f64)
+        .min_by(|a, b| f(*a).total_cmp(&f(*b)))
+        .unwrap();
+
+    let inv_phi = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = ((best - step).max(lo), (best + step).min(hi));
+    for _ in 0..100 {
+        let c = b - inv_phi * (b - a);
+        let d = a + inv_phi * (b - a);
+        if f(c) < f(d) {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+    (a + b) / 2.0
+}
+
+/// Formats quantiles for `Display`, e.g. `[0.025: 4.1, 0.975: 7]`
+pub fn format_quantiles(quantiles: &[Quantile]) -> String {
+    let inner = quantiles
+        .iter()
+        .map(|Quantile { p, x }| format!("{p}: {x}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("[{inner}]")
+}
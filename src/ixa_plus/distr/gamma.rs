@@ -1,4 +1,5 @@
-pub use super::distribution::{ContinuousUnivariate, Distribution};
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use super::fit::{Quantile, check_fit, check_quantiles, format_quantiles, minimize};
 pub use crate::gamma;
 use serde::{Deserialize, Serialize};
 use statrs::{
@@ -22,21 +23,101 @@ macro_rules! gamma {
         }
         .try_into()
     };
+    (mean = $mean:expr, sd = $sd:expr) => {
+        $crate::ixa_plus::distr::gamma::GammaParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, cv = $cv:expr) => {
+        $crate::ixa_plus::distr::gamma::GammaParams::MeanCv {
+            mean: $mean,
+            cv: $cv,
+        }
+        .try_into()
+    };
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// A gamma distribution, given by shape and rate or scale, by its mean and standard
+/// deviation or coefficient of variation, or fit to two or three quantiles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum GammaParams {
     Rate { shape: f64, rate: f64 },
     Scale { shape: f64, scale: f64 },
+    MeanSd { mean: f64, sd: f64 },
+    MeanCv { mean: f64, cv: f64 },
+    Quantiles { quantiles: Vec<Quantile> },
 }
 
-impl TryInto<sd::Gamma> for GammaParams {
-    type Error = sd::GammaError;
-    fn try_into(self) -> Result<sd::Gamma, sd::GammaError> {
-        match self {
-            GammaParams::Rate { shape, rate } => sd::Gamma::new(shape, 1.0 / rate),
-            GammaParams::Scale { shape, scale } => sd::Gamma::new(shape, scale),
+impl GammaParams {
+    /// The shape and rate this parameterization corresponds to
+    pub fn shape_rate(&self) -> Result<(f64, f64), DistrError> {
+        match *self {
+            GammaParams::Rate { shape, rate } => Ok((shape, rate)),
+            GammaParams::Scale { shape, scale } => Ok((shape, 1.0 / scale)),
+            GammaParams::MeanSd { mean, sd } => {
+                if mean <= 0.0 || sd <= 0.0 {
+                    return Err(DistrError(
+                        "Gamma mean and sd must be greater than 0".to_string(),
+                    ));
+                }
+                let shape = (mean / sd).powi(2);
+                Ok((shape, shape / mean))
+            }
+            GammaParams::MeanCv { mean, cv } => {
+                if mean <= 0.0 || cv <= 0.0 {
+                    return Err(DistrError(
+                        "Gamma mean and cv must be greater than 0".to_string(),
+                    ));
+                }
+                let shape = cv.powi(-2);
+                Ok((shape, shape / mean))
+            }
+            GammaParams::Quantiles { ref quantiles } => fit_quantiles(quantiles),
+        }
+    }
+}
+
+fn fit_quantiles(quantiles: &[Quantile]) -> Result<(f64, f64), DistrError> {
+    check_quantiles("Gamma", quantiles, true)?;
+    // x = scale * q(p; shape), where q is the quantile function of Gamma(shape, 1). For each
+    // shape the best log(scale) is the mean of ln(x) - ln(q), so search over log(shape) for
+    // the smallest spread in those differences.
+    let log_residuals = |ln_shape: f64| -> Vec<f64> {
+        let standard = sd::Gamma::new(ln_shape.exp(), 1.0).unwrap();
+        quantiles
+            .iter()
+            .map(|q| q.x.ln() - standard.inverse_cdf(q.p).ln())
+            .collect()
+    };
+    let mean = |r: &[f64]| r.iter().sum::<f64>() / r.len() as f64;
+    let spread = |ln_shape: f64| {
+        let r = log_residuals(ln_shape);
+        let m = mean(&r);
+        let ss: f64 = r.iter().map(|r| (r - m).powi(2)).sum();
+        if ss.is_finite() { ss } else { f64::INFINITY }
+    };
+    let ln_shape = minimize(spread, 0.05f64.ln(), 1e4f64.ln());
+    let ln_scale = mean(&log_residuals(ln_shape));
+    let (shape, rate) = (ln_shape.exp(), (-ln_scale).exp());
+
+    let fitted = sd::Gamma::new(shape, rate)?;
+    check_fit("Gamma", quantiles, |p| fitted.inverse_cdf(p))?;
+    Ok((shape, rate))
+}
+
+impl TryFrom<&GammaParams> for sd::Gamma {
+    type Error = DistrError;
+    fn try_from(params: &GammaParams) -> Result<sd::Gamma, DistrError> {
+        match *params {
+            GammaParams::Rate { shape, rate } => Ok(sd::Gamma::new(shape, 1.0 / rate)?),
+            GammaParams::Scale { shape, scale } => Ok(sd::Gamma::new(shape, scale)?),
+            _ => {
+                let (shape, rate) = params.shape_rate()?;
+                Ok(sd::Gamma::new(shape, rate)?)
+            }
         }
     }
 }
@@ -48,35 +129,40 @@ pub struct Gamma {
 }
 
 impl Gamma {
-    pub fn from_shape_rate(shape: f64, rate: f64) -> Result<Self, sd::GammaError> {
-        let params = GammaParams::Rate { shape, rate };
-        Ok(Self {
-            params,
-            distr: params.clone().try_into()?,
-        })
+    pub fn from_shape_rate(shape: f64, rate: f64) -> Result<Self, DistrError> {
+        Self::try_from(GammaParams::Rate { shape, rate })
     }
-    pub fn from_shape_scale(shape: f64, scale: f64) -> Result<Self, sd::GammaError> {
-        let params = GammaParams::Scale { shape, scale };
-        Ok(Self {
-            params,
-            distr: params.clone().try_into()?,
+    pub fn from_shape_scale(shape: f64, scale: f64) -> Result<Self, DistrError> {
+        Self::try_from(GammaParams::Scale { shape, scale })
+    }
+    pub fn from_mean_sd(mean: f64, sd: f64) -> Result<Self, DistrError> {
+        Self::try_from(GammaParams::MeanSd { mean, sd })
+    }
+    pub fn from_mean_cv(mean: f64, cv: f64) -> Result<Self, DistrError> {
+        Self::try_from(GammaParams::MeanCv { mean, cv })
+    }
+    pub fn from_quantiles(quantiles: &[Quantile]) -> Result<Self, DistrError> {
+        Self::try_from(GammaParams::Quantiles {
+            quantiles: quantiles.to_vec(),
         })
     }
+    pub fn shape(&self) -> f64 {
+        self.distr.shape()
+    }
     pub fn rate(&self) -> f64 {
         match self.params {
             GammaParams::Rate { rate, .. } => rate,
             GammaParams::Scale { scale, .. } => 1.0 / scale,
+            _ => self.distr.rate(),
         }
     }
     pub fn scale(&self) -> f64 {
         match self.params {
             GammaParams::Rate { rate, .. } => 1.0 / rate,
             GammaParams::Scale { scale, .. } => scale,
+            _ => 1.0 / self.distr.rate(),
         }
     }
-    pub fn mean(&self) -> f64 {
-        self.distr.mean().unwrap()
-    }
 }
 
 // Mirror trait methods for convenience
@@ -84,15 +170,34 @@ impl Gamma {
     pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
         <Self as Distribution<f64>>::sample(self, rng)
     }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
 }
 
 impl TryFrom<GammaParams> for Gamma {
-    type Error = sd::GammaError;
+    type Error = DistrError;
 
     fn try_from(params: GammaParams) -> Result<Self, Self::Error> {
-        match params {
-            GammaParams::Rate { shape, rate } => Self::from_shape_rate(shape, rate),
-            GammaParams::Scale { shape, scale } => Self::from_shape_scale(shape, scale),
+        Ok(Self {
+            distr: (&params).try_into()?,
+            params,
+        })
+    }
+}
+
+impl std::fmt::Display for Gamma {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.params {
+            GammaParams::Rate { shape, rate } => write!(f, "Gamma(shape = {shape}, rate = {rate})"),
+            GammaParams::Scale { shape, scale } => {
+                write!(f, "Gamma(shape = {shape}, scale = {scale})")
+            }
+            GammaParams::MeanSd { mean, sd } => write!(f, "Gamma(mean = {mean}, sd = {sd})"),
+            GammaParams::MeanCv { mean, cv } => write!(f, "Gamma(mean = {mean}, cv = {cv})"),
+            GammaParams::Quantiles { quantiles } => {
+                write!(f, "Gamma(quantiles = {})", format_quantiles(quantiles))
+            }
         }
     }
 }
@@ -130,6 +235,14 @@ impl ContinuousUnivariate<f64, f64> for Gamma {
     fn inverse_cdf(&self, p: f64) -> f64 {
         self.distr.inverse_cdf(p)
     }
+    fn mean(&self) -> f64 {
+        self.distr.mean().unwrap()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // x times the density is the mean times the density with one more shape
+        let size_biased = sd::Gamma::new(self.distr.shape() + 1.0, self.distr.rate()).unwrap();
+        self.mean() * (size_biased.cdf(upper) - size_biased.cdf(lower))
+    }
 }
 
 impl Distribution<f64> for Gamma {
@@ -137,3 +250,72 @@ impl Distribution<f64> for Gamma {
         self.distr.sample(rng)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_shape_rate_and_scale_agree() {
+        let by_rate: Gamma = gamma!(shape = 3.0, rate = 0.5).unwrap();
+        let by_scale: Gamma = gamma!(shape = 3.0, scale = 2.0).unwrap();
+        assert_relative_eq!(by_rate.mean(), by_scale.mean());
+        assert_relative_eq!(by_rate.cdf(4.0), by_scale.cdf(4.0));
+    }
+
+    #[test]
+    fn test_mean_sd_and_mean_cv() {
+        let d: Gamma = gamma!(mean = 5.2, sd = 1.3).unwrap();
+        assert_relative_eq!(d.mean(), 5.2, epsilon = 1e-12);
+        assert_relative_eq!(d.distr.std_dev().unwrap(), 1.3, epsilon = 1e-12);
+        let d: Gamma = gamma!(mean = 5.2, cv = 0.25).unwrap();
+        assert_relative_eq!(d.distr.std_dev().unwrap(), 1.3, epsilon = 1e-12);
+        assert_eq!(d.to_string(), "Gamma(mean = 5.2, cv = 0.25)");
+    }
+
+    #[test]
+    fn test_fit_two_quantiles() {
+        let quantiles = [Quantile { p: 0.025, x: 4.1 }, Quantile { p: 0.975, x: 7.0 }];
+        let d = Gamma::from_quantiles(&quantiles).unwrap();
+        for q in quantiles {
+            assert_relative_eq!(d.inverse_cdf(q.p), q.x, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_fit_three_quantiles() {
+        let truth: Gamma = gamma!(shape = 4.0, rate = 0.8).unwrap();
+        let quantiles = [0.05, 0.5, 0.95].map(|p| Quantile {
+            p,
+            x: truth.inverse_cdf(p),
+        });
+        let d = Gamma::from_quantiles(&quantiles).unwrap();
+        assert_relative_eq!(d.shape(), 4.0, epsilon = 1e-3);
+        assert_relative_eq!(d.rate(), truth.distr.rate(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_inconsistent_quantiles() {
+        // A median far below the midpoint of a narrow interval isn't gamma shaped
+        let quantiles = [
+            Quantile { p: 0.05, x: 10.0 },
+            Quantile { p: 0.5, x: 10.1 },
+            Quantile { p: 0.95, x: 20.0 },
+        ];
+        let error = Gamma::from_quantiles(&quantiles).unwrap_err();
+        assert!(error.0.contains("inconsistent"), "{error}");
+
+        let decreasing = [Quantile { p: 0.1, x: 5.0 }, Quantile { p: 0.9, x: 4.0 }];
+        assert!(Gamma::from_quantiles(&decreasing).is_err());
+    }
+
+    #[test]
+    fn test_serialization_preserves_form() {
+        let d: Gamma =
+            toml::from_str("quantiles = [{ p = 0.025, x = 4.1 }, { p = 0.975, x = 7.0 }]").unwrap();
+        let written: Gamma = toml::from_str(&toml::to_string(&d).unwrap()).unwrap();
+        assert!(matches!(written.params, GammaParams::Quantiles { .. }));
+        assert_eq!(d.to_string(), "Gamma(quantiles = [0.025: 4.1, 0.975: 7])");
+    }
+}
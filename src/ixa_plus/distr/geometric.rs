@@ -0,0 +1,169 @@
+pub use super::distribution::{DiscreteUnivariate, DistrError, Distribution};
+pub use crate::geometric;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{self as sd, Discrete, DiscreteCDF};
+
+#[macro_export]
+macro_rules! geometric {
+    (p = $p:expr) => {
+        $crate::ixa_plus::distr::geometric::GeometricParams::P { p: $p }.try_into()
+    };
+    (mean = $mean:expr) => {
+        $crate::ixa_plus::distr::geometric::GeometricParams::Mean { mean: $mean }.try_into()
+    };
+}
+
+/// The number of failures before the first success, so the support starts at 0. Given by
+/// the success probability `p` or by the mean, `(1 - p) / p`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum GeometricParams {
+    P { p: f64 },
+    Mean { mean: f64 },
+}
+
+impl GeometricParams {
+    /// The success probability
+    pub fn p(&self) -> Result<f64, DistrError> {
+        let p = match *self {
+            GeometricParams::P { p } => p,
+            GeometricParams::Mean { mean } => {
+                if mean.is_nan() || mean < 0.0 {
+                    return Err(DistrError(format!(
+                        "Geometric mean must be at least 0, got {mean}"
+                    )));
+                }
+                1.0 / (1.0 + mean)
+            }
+        };
+        if !(p > 0.0 && p <= 1.0) {
+            return Err(DistrError(format!(
+                "Geometric p must be in (0, 1], got {p}"
+            )));
+        }
+        Ok(p)
+    }
+}
+
+// statrs' Geometric counts trials rather than failures, but a negative binomial with a
+// single success is exactly the distribution we want.
+impl TryFrom<GeometricParams> for sd::NegativeBinomial {
+    type Error = DistrError;
+    fn try_from(params: GeometricParams) -> Result<sd::NegativeBinomial, DistrError> {
+        Ok(sd::NegativeBinomial::new(1.0, params.p()?)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Geometric {
+    params: GeometricParams,
+    distr: sd::NegativeBinomial,
+}
+
+impl Geometric {
+    pub fn from_p(p: f64) -> Result<Self, DistrError> {
+        Self::try_from(GeometricParams::P { p })
+    }
+    pub fn from_mean(mean: f64) -> Result<Self, DistrError> {
+        Self::try_from(GeometricParams::Mean { mean })
+    }
+    pub fn p(&self) -> f64 {
+        self.distr.p()
+    }
+}
+
+// Mirror trait methods for convenience
+impl Geometric {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        <Self as Distribution<u64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as DiscreteUnivariate<u64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<GeometricParams> for Geometric {
+    type Error = DistrError;
+
+    fn try_from(params: GeometricParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Geometric {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            GeometricParams::P { p } => write!(f, "Geometric(p = {p})"),
+            GeometricParams::Mean { mean } => write!(f, "Geometric(mean = {mean})"),
+        }
+    }
+}
+
+impl Serialize for Geometric {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Geometric {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = GeometricParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl DiscreteUnivariate<u64, f64> for Geometric {
+    fn pmf(&self, x: u64) -> f64 {
+        self.distr.pmf(x)
+    }
+    fn ln_pmf(&self, x: u64) -> f64 {
+        self.distr.ln_pmf(x)
+    }
+    fn cdf(&self, x: u64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> u64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        (1.0 - self.p()) / self.p()
+    }
+}
+
+impl Distribution<u64> for Geometric {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        // Inverse transform of the survival function P(X >= x) = (1 - p)^x
+        let p = self.p();
+        if p == 1.0 {
+            return 0;
+        }
+        let u = 1.0 - rng.random::<f64>();
+        (u.ln() / (1.0 - p).ln()).floor() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_counts_failures_from_zero() {
+        let d: Geometric = geometric!(mean = 3.0).unwrap();
+        assert_relative_eq!(d.p(), 0.25);
+        assert_relative_eq!(d.pmf(0), 0.25, epsilon = 1e-12);
+        let n = 100_000;
+        let mut rng = rand::rng();
+        let mean = (0..n).map(|_| d.sample(&mut rng) as f64).sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 3.0, epsilon = 0.1);
+    }
+}
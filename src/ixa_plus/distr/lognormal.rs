@@ -0,0 +1,249 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use super::fit::{Quantile, check_fit, check_quantiles, fit_line, format_quantiles};
+pub use crate::lognormal;
+use serde::{Deserialize, Serialize};
+use statrs::{
+    distribution::{self as sd, Continuous, ContinuousCDF},
+    statistics::Distribution as StatisticsDistribution,
+};
+
+#[macro_export]
+macro_rules! lognormal {
+    (mu = $mu:expr, sigma = $sigma:expr) => {
+        $crate::ixa_plus::distr::lognormal::LogNormalParams::MuSigma {
+            mu: $mu,
+            sigma: $sigma,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, sd = $sd:expr) => {
+        $crate::ixa_plus::distr::lognormal::LogNormalParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, cv = $cv:expr) => {
+        $crate::ixa_plus::distr::lognormal::LogNormalParams::MeanCv {
+            mean: $mean,
+            cv: $cv,
+        }
+        .try_into()
+    };
+}
+
+/// A log-normal distribution, given either by the mean and standard deviation of the
+/// underlying normal (`mu`, `sigma`), by its own mean and standard deviation or
+/// coefficient of variation, or fit to two or three quantiles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum LogNormalParams {
+    MuSigma { mu: f64, sigma: f64 },
+    MeanSd { mean: f64, sd: f64 },
+    MeanCv { mean: f64, cv: f64 },
+    Quantiles { quantiles: Vec<Quantile> },
+}
+
+impl LogNormalParams {
+    /// The mean and standard deviation of the underlying normal distribution
+    pub fn mu_sigma(&self) -> Result<(f64, f64), DistrError> {
+        match *self {
+            LogNormalParams::MuSigma { mu, sigma } => Ok((mu, sigma)),
+            LogNormalParams::MeanSd { mean, sd } => {
+                if mean <= 0.0 || sd <= 0.0 {
+                    return Err(DistrError(
+                        "LogNormal mean and sd must be greater than 0".to_string(),
+                    ));
+                }
+                Ok(mu_sigma_from_mean_cv(mean, sd / mean))
+            }
+            LogNormalParams::MeanCv { mean, cv } => {
+                if mean <= 0.0 || cv <= 0.0 {
+                    return Err(DistrError(
+                        "LogNormal mean and cv must be greater than 0".to_string(),
+                    ));
+                }
+                Ok(mu_sigma_from_mean_cv(mean, cv))
+            }
+            LogNormalParams::Quantiles { ref quantiles } => {
+                check_quantiles("LogNormal", quantiles, true)?;
+                // ln(x) = mu + sigma * z, where z is the standard normal quantile of p
+                let standard = sd::Normal::standard();
+                let points = quantiles
+                    .iter()
+                    .map(|q| (standard.inverse_cdf(q.p), q.x.ln()))
+                    .collect::<Vec<_>>();
+                let (mu, sigma) = fit_line(&points);
+                let fitted = sd::LogNormal::new(mu, sigma)?;
+                check_fit("LogNormal", quantiles, |p| fitted.inverse_cdf(p))?;
+                Ok((mu, sigma))
+            }
+        }
+    }
+}
+
+fn mu_sigma_from_mean_cv(mean: f64, cv: f64) -> (f64, f64) {
+    let sigma_sq = (1.0 + cv.powi(2)).ln();
+    (mean.ln() - sigma_sq / 2.0, sigma_sq.sqrt())
+}
+
+impl TryFrom<&LogNormalParams> for sd::LogNormal {
+    type Error = DistrError;
+    fn try_from(params: &LogNormalParams) -> Result<sd::LogNormal, DistrError> {
+        let (mu, sigma) = params.mu_sigma()?;
+        Ok(sd::LogNormal::new(mu, sigma)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogNormal {
+    params: LogNormalParams,
+    distr: sd::LogNormal,
+    mu: f64,
+    sigma: f64,
+}
+
+impl LogNormal {
+    pub fn from_mu_sigma(mu: f64, sigma: f64) -> Result<Self, DistrError> {
+        Self::try_from(LogNormalParams::MuSigma { mu, sigma })
+    }
+    pub fn from_mean_sd(mean: f64, sd: f64) -> Result<Self, DistrError> {
+        Self::try_from(LogNormalParams::MeanSd { mean, sd })
+    }
+    pub fn from_mean_cv(mean: f64, cv: f64) -> Result<Self, DistrError> {
+        Self::try_from(LogNormalParams::MeanCv { mean, cv })
+    }
+    pub fn from_quantiles(quantiles: &[Quantile]) -> Result<Self, DistrError> {
+        Self::try_from(LogNormalParams::Quantiles {
+            quantiles: quantiles.to_vec(),
+        })
+    }
+    pub fn mu(&self) -> f64 {
+        self.mu
+    }
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+// Mirror trait methods for convenience
+impl LogNormal {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<LogNormalParams> for LogNormal {
+    type Error = DistrError;
+
+    fn try_from(params: LogNormalParams) -> Result<Self, Self::Error> {
+        let (mu, sigma) = params.mu_sigma()?;
+        Ok(Self {
+            distr: sd::LogNormal::new(mu, sigma)?,
+            params,
+            mu,
+            sigma,
+        })
+    }
+}
+
+impl std::fmt::Display for LogNormal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.params {
+            LogNormalParams::MuSigma { mu, sigma } => {
+                write!(f, "LogNormal(mu = {mu}, sigma = {sigma})")
+            }
+            LogNormalParams::MeanSd { mean, sd } => {
+                write!(f, "LogNormal(mean = {mean}, sd = {sd})")
+            }
+            LogNormalParams::MeanCv { mean, cv } => {
+                write!(f, "LogNormal(mean = {mean}, cv = {cv})")
+            }
+            LogNormalParams::Quantiles { quantiles } => {
+                write!(f, "LogNormal(quantiles = {})", format_quantiles(quantiles))
+            }
+        }
+    }
+}
+
+impl Serialize for LogNormal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogNormal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = LogNormalParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for LogNormal {
+    fn pdf(&self, x: f64) -> f64 {
+        self.distr.pdf(x)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.distr.ln_pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        self.distr.mean().unwrap()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // x times the density is the mean times the density with mu moved up by sigma^2
+        let size_biased = sd::LogNormal::new(self.mu + self.sigma.powi(2), self.sigma).unwrap();
+        self.mean() * (size_biased.cdf(upper) - size_biased.cdf(lower))
+    }
+}
+
+impl Distribution<f64> for LogNormal {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mean_sd_matches_moments() {
+        let d: LogNormal = lognormal!(mean = 5.2, sd = 1.5).unwrap();
+        assert_relative_eq!(d.mean(), 5.2, epsilon = 1e-12);
+        assert_relative_eq!(d.distr.std_dev().unwrap(), 1.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_mean_sd_rejects_non_positive_mean() {
+        let result: Result<LogNormal, _> = lognormal!(mean = 0.0, sd = 1.0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_fit_quantiles() {
+        let truth = LogNormal::from_mu_sigma(1.5, 0.4).unwrap();
+        let quantiles = [0.025, 0.5, 0.975].map(|p| Quantile {
+            p,
+            x: truth.inverse_cdf(p),
+        });
+        let d = LogNormal::from_quantiles(&quantiles).unwrap();
+        assert_relative_eq!(d.mu(), 1.5, epsilon = 1e-9);
+        assert_relative_eq!(d.sigma(), 0.4, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,236 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use serde::{Deserialize, Serialize};
+
+/// One component of a mixture, chosen with probability proportional to `weight`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Component<D> {
+    pub weight: f64,
+    pub distribution: D,
+}
+
+/// A weighted mixture of distributions, e.g. 80% low shedders and 20% high shedders as
+/// `components = [{ weight = 0.8, distribution = { ... } }, { weight = 0.2, ... }]`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MixtureParams<D> {
+    pub components: Vec<Component<D>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mixture<D> {
+    params: MixtureParams<D>,
+    // Weights normalized to sum to 1, and their running total
+    probabilities: Vec<f64>,
+    cumulative: Vec<f64>,
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> Mixture<D> {
+    pub fn new(components: Vec<Component<D>>) -> Result<Self, DistrError> {
+        Self::try_from(MixtureParams { components })
+    }
+    pub fn components(&self) -> &[Component<D>] {
+        &self.params.components
+    }
+    /// The probability of drawing from each component
+    pub fn probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+}
+
+// Mirror trait methods for convenience
+impl<D: ContinuousUnivariate<f64, f64> + Distribution<f64>> Mixture<D> {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> TryFrom<MixtureParams<D>> for Mixture<D> {
+    type Error = DistrError;
+
+    fn try_from(params: MixtureParams<D>) -> Result<Self, Self::Error> {
+        if params.components.is_empty() {
+            return Err(DistrError(
+                "Mixture needs at least one component".to_string(),
+            ));
+        }
+        if let Some(c) = params
+            .components
+            .iter()
+            .find(|c| !c.weight.is_finite() || c.weight < 0.0)
+        {
+            return Err(DistrError(format!(
+                "Mixture weights must be finite and non-negative, got {}",
+                c.weight
+            )));
+        }
+        let total: f64 = params.components.iter().map(|c| c.weight).sum();
+        if total <= 0.0 {
+            return Err(DistrError(
+                "Mixture needs at least one component with positive weight".to_string(),
+            ));
+        }
+        let probabilities: Vec<f64> = params.components.iter().map(|c| c.weight / total).collect();
+        let cumulative = probabilities
+            .iter()
+            .scan(0.0, |acc, p| {
+                *acc += p;
+                Some(*acc)
+            })
+            .collect();
+        Ok(Self {
+            params,
+            probabilities,
+            cumulative,
+        })
+    }
+}
+
+impl<D: std::fmt::Display> std::fmt::Display for Mixture<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let components = self
+            .params
+            .components
+            .iter()
+            .map(|c| format!("{} * {}", c.weight, c.distribution))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "Mixture({components})")
+    }
+}
+
+impl<D: Serialize> Serialize for Mixture<D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de, D> Deserialize<'de> for Mixture<D>
+where
+    D: Deserialize<'de> + ContinuousUnivariate<f64, f64>,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: serde::Deserializer<'de>,
+    {
+        let params = MixtureParams::<D>::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> Mixture<D> {
+    // Sums `f` over the components, weighted by their probabilities
+    fn weighted_sum(&self, f: impl Fn(&D) -> f64) -> f64 {
+        self.params
+            .components
+            .iter()
+            .zip(&self.probabilities)
+            .filter(|(_, p)| **p > 0.0)
+            .map(|(c, p)| p * f(&c.distribution))
+            .sum()
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> ContinuousUnivariate<f64, f64> for Mixture<D> {
+    fn pdf(&self, x: f64) -> f64 {
+        self.weighted_sum(|d| d.pdf(x))
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.pdf(x).ln()
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.weighted_sum(|d| d.cdf(x))
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        // The mixture's quantile lies between the smallest and largest component quantiles,
+        // since the cdf is a weighted average of the component cdfs. Bisect between them.
+        let quantiles = self
+            .params
+            .components
+            .iter()
+            .zip(&self.probabilities)
+            .filter(|(_, w)| **w > 0.0)
+            .map(|(c, _)| c.distribution.inverse_cdf(p));
+        let (mut lo, mut hi) = quantiles.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| {
+            (lo.min(x), hi.max(x))
+        });
+        if !lo.is_finite() || !hi.is_finite() {
+            return if p < 0.5 { lo } else { hi };
+        }
+        for _ in 0..100 {
+            let mid = (lo + hi) / 2.0;
+            if self.cdf(mid) < p {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.0
+    }
+    fn mean(&self) -> f64 {
+        self.weighted_sum(|d| d.mean())
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        self.weighted_sum(|d| d.partial_expectation(lower, upper))
+    }
+}
+
+impl<D: Distribution<f64>> Distribution<f64> for Mixture<D> {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let u = rng.random::<f64>();
+        let i = self.cumulative.partition_point(|c| *c <= u);
+        let i = i.min(self.cumulative.len() - 1);
+        self.params.components[i].distribution.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ixa_plus::distr::exponential::Exponential;
+    use approx::assert_relative_eq;
+
+    fn shedders() -> Mixture<Exponential> {
+        Mixture::new(vec![
+            Component {
+                weight: 8.0,
+                distribution: Exponential::from_mean(1.0).unwrap(),
+            },
+            Component {
+                weight: 2.0,
+                distribution: Exponential::from_mean(6.0).unwrap(),
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mixture_moments() {
+        let d = shedders();
+        assert_relative_eq!(d.mean(), 0.8 + 0.2 * 6.0);
+        let x: f64 = 2.0;
+        let expected_cdf = 0.8 * (1.0 - (-x).exp()) + 0.2 * (1.0 - (-x / 6.0f64).exp());
+        assert_relative_eq!(d.cdf(x), expected_cdf, epsilon = 1e-12);
+        assert_relative_eq!(d.inverse_cdf(expected_cdf), x, epsilon = 1e-9);
+
+        let n = 200_000;
+        let mut rng = rand::rng();
+        let mean = (0..n).map(|_| d.sample(&mut rng)).sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 2.0, epsilon = 0.05);
+    }
+
+    #[test]
+    fn test_invalid_weights() {
+        let component = |weight| Component {
+            weight,
+            distribution: Exponential::from_mean(1.0).unwrap(),
+        };
+        assert!(Mixture::<Exponential>::new(vec![]).is_err());
+        assert!(Mixture::new(vec![component(0.0)]).is_err());
+        assert!(Mixture::new(vec![component(1.0), component(-1.0)]).is_err());
+    }
+}
@@ -1,2 +1,19 @@
+pub mod any;
+pub mod beta;
+pub mod categorical;
+pub mod constant;
+pub mod copula;
 mod distribution;
+pub mod exponential;
+pub mod fit;
 pub mod gamma;
+pub mod geometric;
+pub mod lognormal;
+pub mod mixture;
+pub mod negative_binomial;
+pub mod normal;
+pub mod poisson;
+pub mod shifted;
+pub mod truncated;
+pub mod uniform;
+pub mod weibull;
@@ -0,0 +1,215 @@
+pub use super::distribution::{DiscreteUnivariate, DistrError, Distribution};
+use super::fit::minimize;
+pub use crate::negative_binomial;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{self as sd, Discrete, DiscreteCDF};
+
+#[macro_export]
+macro_rules! negative_binomial {
+    (mean = $mean:expr, k = $k:expr) => {
+        $crate::ixa_plus::distr::negative_binomial::NegativeBinomialParams::MeanK {
+            mean: $mean,
+            k: $k,
+        }
+        .try_into()
+    };
+}
+
+/// A negative binomial distribution given by its mean and dispersion `k`, so that the
+/// variance is `mean + mean^2 / k`. Smaller `k` means more overdispersion; as `k` grows
+/// it approaches a Poisson.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum NegativeBinomialParams {
+    MeanK { mean: f64, k: f64 },
+}
+
+impl TryFrom<NegativeBinomialParams> for sd::NegativeBinomial {
+    type Error = DistrError;
+    fn try_from(params: NegativeBinomialParams) -> Result<sd::NegativeBinomial, DistrError> {
+        match params {
+            NegativeBinomialParams::MeanK { mean, k } => {
+                if mean.is_nan() || mean < 0.0 || k.is_nan() || k <= 0.0 {
+                    return Err(DistrError(format!(
+                        "NegativeBinomial mean must be at least 0 and k greater than 0, got \
+                         mean = {mean}, k = {k}"
+                    )));
+                }
+                // statrs counts failures before the r-th success, with success probability p
+                Ok(sd::NegativeBinomial::new(k, k / (k + mean))?)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NegativeBinomial {
+    params: NegativeBinomialParams,
+    distr: sd::NegativeBinomial,
+}
+
+impl NegativeBinomial {
+    pub fn from_mean_k(mean: f64, k: f64) -> Result<Self, DistrError> {
+        Self::try_from(NegativeBinomialParams::MeanK { mean, k })
+    }
+    pub fn k(&self) -> f64 {
+        self.distr.r()
+    }
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        mean + mean * mean / self.k()
+    }
+    /// The maximum likelihood estimate of `k` for observed counts, with the mean fixed at
+    /// the sample mean. Counts spread no more than a Poisson give infinity; returns None if
+    /// there are no counts or they are all zero.
+    pub fn fit_k(counts: &[u64]) -> Option<f64> {
+        if counts.is_empty() {
+            return None;
+        }
+        let n = counts.len() as f64;
+        let mean = counts.iter().sum::<u64>() as f64 / n;
+        if mean == 0.0 {
+            return None;
+        }
+        let variance = counts
+            .iter()
+            .map(|x| (*x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        if variance <= mean {
+            return Some(f64::INFINITY);
+        }
+        // Tally each distinct count so the likelihood is evaluated once per value
+        let mut tally = std::collections::BTreeMap::new();
+        for x in counts {
+            *tally.entry(*x).or_insert(0.0) += 1.0;
+        }
+        let neg_log_likelihood = |ln_k: f64| {
+            let d = Self::from_mean_k(mean, ln_k.exp()).unwrap();
+            -tally.iter().map(|(x, w)| w * d.ln_pmf(*x)).sum::<f64>()
+        };
+        Some(minimize(neg_log_likelihood, -10.0, 10.0).exp())
+    }
+}
+
+// Mirror trait methods for convenience
+impl NegativeBinomial {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        <Self as Distribution<u64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as DiscreteUnivariate<u64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<NegativeBinomialParams> for NegativeBinomial {
+    type Error = DistrError;
+
+    fn try_from(params: NegativeBinomialParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for NegativeBinomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            NegativeBinomialParams::MeanK { mean, k } => {
+                write!(f, "NegativeBinomial(mean = {mean}, k = {k})")
+            }
+        }
+    }
+}
+
+impl Serialize for NegativeBinomial {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NegativeBinomial {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = NegativeBinomialParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl DiscreteUnivariate<u64, f64> for NegativeBinomial {
+    fn pmf(&self, x: u64) -> f64 {
+        self.distr.pmf(x)
+    }
+    fn ln_pmf(&self, x: u64) -> f64 {
+        self.distr.ln_pmf(x)
+    }
+    fn cdf(&self, x: u64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> u64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        match self.params {
+            NegativeBinomialParams::MeanK { mean, .. } => mean,
+        }
+    }
+}
+
+impl Distribution<u64> for NegativeBinomial {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        // statrs' sampler passes the gamma mixing distribution a scale where it expects a
+        // rate, so draw from the gamma-Poisson mixture directly
+        let NegativeBinomialParams::MeanK { mean, k } = self.params;
+        if mean == 0.0 {
+            return 0;
+        }
+        let lambda = sd::Gamma::new(k, k / mean).unwrap().sample(rng);
+        if lambda <= 0.0 {
+            return 0;
+        }
+        Distribution::<u64>::sample(&sd::Poisson::new(lambda).unwrap(), rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mean_k_moments() {
+        let d: NegativeBinomial = negative_binomial!(mean = 2.5, k = 0.5).unwrap();
+        assert_relative_eq!(d.variance(), 2.5 + 2.5 * 2.5 / 0.5);
+        let n = 200_000;
+        let mut rng = rand::rng();
+        let samples: Vec<f64> = (0..n).map(|_| d.sample(&mut rng) as f64).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 2.5, epsilon = 0.1);
+    }
+
+    #[test]
+    fn test_fit_recovers_k() {
+        let d: NegativeBinomial = negative_binomial!(mean = 2.0, k = 0.3).unwrap();
+        let mut rng = rand::rng();
+        let counts: Vec<u64> = (0..20_000).map(|_| d.sample(&mut rng)).collect();
+        let k = NegativeBinomial::fit_k(&counts).unwrap();
+        assert_relative_eq!(k, 0.3, epsilon = 0.03);
+
+        let poisson_like = NegativeBinomial::fit_k(&[1, 2, 1, 2, 1, 2]).unwrap();
+        assert!(poisson_like.is_infinite());
+        assert!(NegativeBinomial::fit_k(&[0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_invalid_k() {
+        let result: Result<NegativeBinomial, _> = negative_binomial!(mean = 2.0, k = 0.0);
+        assert!(result.is_err());
+    }
+}
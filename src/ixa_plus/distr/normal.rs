@@ -0,0 +1,277 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use super::fit::{Quantile, check_fit, check_quantiles, fit_line, format_quantiles};
+use super::truncated::Truncation;
+pub use crate::normal;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{self as sd, Continuous, ContinuousCDF};
+
+#[macro_export]
+macro_rules! normal {
+    (mean = $mean:expr, sd = $sd:expr) => {
+        $crate::ixa_plus::distr::normal::NormalParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+            lower: None,
+            upper: None,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, sd = $sd:expr, lower = $lower:expr, upper = $upper:expr) => {
+        $crate::ixa_plus::distr::normal::NormalParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+            lower: Some($lower),
+            upper: Some($upper),
+        }
+        .try_into()
+    };
+}
+
+/// A normal distribution, optionally truncated to `[lower, upper]`. Either bound may be
+/// left out, e.g. `{ mean = 5.0, sd = 2.0, lower = 0.0 }` for a non-negative quantity.
+/// An untruncated normal can also be fit to two or three quantiles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum NormalParams {
+    MeanSd {
+        mean: f64,
+        sd: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lower: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        upper: Option<f64>,
+    },
+    Quantiles {
+        quantiles: Vec<Quantile>,
+    },
+}
+
+fn fit_quantiles(quantiles: &[Quantile]) -> Result<(f64, f64), DistrError> {
+    check_quantiles("Normal", quantiles, false)?;
+    // x = mean + sd * z, where z is the standard normal quantile of p
+    let standard = sd::Normal::standard();
+    let points = quantiles
+        .iter()
+        .map(|q| (standard.inverse_cdf(q.p), q.x))
+        .collect::<Vec<_>>();
+    let (mean, sd) = fit_line(&points);
+    let fitted = sd::Normal::new(mean, sd)?;
+    check_fit("Normal", quantiles, |p| fitted.inverse_cdf(p))?;
+    Ok((mean, sd))
+}
+
+#[derive(Debug, Clone)]
+pub struct Normal {
+    params: NormalParams,
+    distr: sd::Normal,
+    mu: f64,
+    sigma: f64,
+    truncation: Truncation,
+}
+
+impl Normal {
+    pub fn from_mean_sd(mean: f64, sd: f64) -> Result<Self, DistrError> {
+        Self::try_from(NormalParams::MeanSd {
+            mean,
+            sd,
+            lower: None,
+            upper: None,
+        })
+    }
+    pub fn truncated(mean: f64, sd: f64, lower: f64, upper: f64) -> Result<Self, DistrError> {
+        Self::try_from(NormalParams::MeanSd {
+            mean,
+            sd,
+            lower: Some(lower),
+            upper: Some(upper),
+        })
+    }
+    pub fn from_quantiles(quantiles: &[Quantile]) -> Result<Self, DistrError> {
+        Self::try_from(NormalParams::Quantiles {
+            quantiles: quantiles.to_vec(),
+        })
+    }
+    pub fn lower(&self) -> f64 {
+        self.truncation.lower()
+    }
+    pub fn upper(&self) -> f64 {
+        self.truncation.upper()
+    }
+    pub fn is_truncated(&self) -> bool {
+        self.truncation.is_truncated()
+    }
+
+    // The integral of x * pdf(x) from `lower` to `upper` before truncation
+    fn untruncated_partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        let standard = sd::Normal::standard();
+        let alpha = (lower - self.mu) / self.sigma;
+        let beta = (upper - self.mu) / self.sigma;
+        self.mu * (standard.cdf(beta) - standard.cdf(alpha))
+            + self.sigma * (standard.pdf(alpha) - standard.pdf(beta))
+    }
+}
+
+// Mirror trait methods for convenience
+impl Normal {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    /// The mean after truncation
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<NormalParams> for Normal {
+    type Error = DistrError;
+
+    fn try_from(params: NormalParams) -> Result<Self, Self::Error> {
+        let (mean, sd, lower, upper) = match &params {
+            NormalParams::MeanSd {
+                mean,
+                sd,
+                lower,
+                upper,
+            } => (*mean, *sd, *lower, *upper),
+            NormalParams::Quantiles { quantiles } => {
+                let (mean, sd) = fit_quantiles(quantiles)?;
+                (mean, sd, None, None)
+            }
+        };
+        let distr = sd::Normal::new(mean, sd)?;
+        let truncation = Truncation::new("Normal", lower, upper, |x| distr.cdf(x))?;
+        Ok(Self {
+            params,
+            distr,
+            mu: mean,
+            sigma: sd,
+            truncation,
+        })
+    }
+}
+
+impl std::fmt::Display for Normal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.params {
+            NormalParams::MeanSd {
+                mean,
+                sd,
+                lower,
+                upper,
+            } => {
+                write!(f, "Normal(mean = {mean}, sd = {sd}")?;
+                if let Some(lower) = lower {
+                    write!(f, ", lower = {lower}")?;
+                }
+                if let Some(upper) = upper {
+                    write!(f, ", upper = {upper}")?;
+                }
+                write!(f, ")")
+            }
+            NormalParams::Quantiles { quantiles } => {
+                write!(f, "Normal(quantiles = {})", format_quantiles(quantiles))
+            }
+        }
+    }
+}
+
+impl Serialize for Normal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Normal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = NormalParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Normal {
+    fn pdf(&self, x: f64) -> f64 {
+        self.truncation.pdf(x, |x| self.distr.pdf(x))
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.truncation.ln_pdf(x, |x| self.distr.ln_pdf(x))
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.truncation.cdf(x, |x| self.distr.cdf(x))
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.truncation
+            .inverse_cdf(p, |p| self.distr.inverse_cdf(p))
+    }
+    fn mean(&self) -> f64 {
+        if !self.is_truncated() {
+            return self.mu;
+        }
+        self.truncation
+            .mean(|lower, upper| self.untruncated_partial_expectation(lower, upper))
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        self.truncation
+            .partial_expectation(lower, upper, |lower, upper| {
+                self.untruncated_partial_expectation(lower, upper)
+            })
+    }
+}
+
+impl Distribution<f64> for Normal {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        if !self.is_truncated() {
+            return self.distr.sample(rng);
+        }
+        // Inverse transform sampling keeps draws within the bounds without rejection
+        self.inverse_cdf(rng.random::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_truncated_mean() {
+        // A standard normal truncated to [0, inf) is the half-normal, with mean sqrt(2 / pi)
+        let d: Normal = normal!(mean = 0.0, sd = 1.0, lower = 0.0, upper = f64::INFINITY).unwrap();
+        assert_relative_eq!(
+            d.mean(),
+            (2.0 / std::f64::consts::PI).sqrt(),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(d.cdf(0.0), 0.0);
+        assert_relative_eq!(d.pdf(-0.5), 0.0);
+    }
+
+    #[test]
+    fn test_truncated_samples_stay_in_bounds() {
+        let d: Normal = normal!(mean = 5.0, sd = 3.0, lower = 1.0, upper = 6.0).unwrap();
+        let mut rng = rand::rng();
+        for _ in 0..1000 {
+            let x = d.sample(&mut rng);
+            assert!((1.0..=6.0).contains(&x), "{x}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_bounds() {
+        let result: Result<Normal, _> = normal!(mean = 0.0, sd = 1.0, lower = 2.0, upper = 1.0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_fit_quantiles() {
+        let quantiles = [Quantile { p: 0.025, x: 4.1 }, Quantile { p: 0.975, x: 7.0 }];
+        let d = Normal::from_quantiles(&quantiles).unwrap();
+        assert_relative_eq!(d.mean(), 5.55, epsilon = 1e-9);
+        assert_relative_eq!(d.inverse_cdf(0.975), 7.0, epsilon = 1e-9);
+    }
+}
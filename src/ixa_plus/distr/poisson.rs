@@ -0,0 +1,133 @@
+pub use super::distribution::{DiscreteUnivariate, DistrError, Distribution};
+pub use crate::poisson;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{self as sd, Discrete, DiscreteCDF};
+
+#[macro_export]
+macro_rules! poisson {
+    (mean = $mean:expr) => {
+        $crate::ixa_plus::distr::poisson::PoissonParams::Mean { mean: $mean }.try_into()
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum PoissonParams {
+    Mean { mean: f64 },
+}
+
+impl TryFrom<PoissonParams> for sd::Poisson {
+    type Error = DistrError;
+    fn try_from(params: PoissonParams) -> Result<sd::Poisson, DistrError> {
+        match params {
+            PoissonParams::Mean { mean } => Ok(sd::Poisson::new(mean)?),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Poisson {
+    params: PoissonParams,
+    distr: sd::Poisson,
+}
+
+impl Poisson {
+    pub fn from_mean(mean: f64) -> Result<Self, DistrError> {
+        Self::try_from(PoissonParams::Mean { mean })
+    }
+}
+
+// Mirror trait methods for convenience
+impl Poisson {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        <Self as Distribution<u64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as DiscreteUnivariate<u64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<PoissonParams> for Poisson {
+    type Error = DistrError;
+
+    fn try_from(params: PoissonParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Poisson {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            PoissonParams::Mean { mean } => write!(f, "Poisson(mean = {mean})"),
+        }
+    }
+}
+
+impl Serialize for Poisson {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Poisson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = PoissonParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl DiscreteUnivariate<u64, f64> for Poisson {
+    fn pmf(&self, x: u64) -> f64 {
+        self.distr.pmf(x)
+    }
+    fn ln_pmf(&self, x: u64) -> f64 {
+        self.distr.ln_pmf(x)
+    }
+    fn cdf(&self, x: u64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> u64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        self.distr.lambda()
+    }
+}
+
+impl Distribution<u64> for Poisson {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mean() {
+        let d: Poisson = poisson!(mean = 2.5).unwrap();
+        assert_relative_eq!(d.mean(), 2.5);
+        assert_relative_eq!(d.pmf(0), (-2.5f64).exp(), epsilon = 1e-12);
+        assert_relative_eq!(d.cdf(1), 3.5 * (-2.5f64).exp(), epsilon = 1e-12);
+        assert_eq!(d.inverse_cdf(d.cdf(1)), 1);
+
+        let n = 100_000;
+        let mut rng = rand::rng();
+        let mean = (0..n).map(|_| d.sample(&mut rng) as f64).sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 2.5, epsilon = 0.05);
+
+        let result: Result<Poisson, _> = poisson!(mean = 0.0);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,168 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use serde::{Deserialize, Serialize};
+
+/// `distribution` moved right by `shift`, e.g.
+/// `{ shift = 1.0, distribution = { type = "gamma", shape = 3.0, rate = 1.0 } }`
+/// for one day plus a gamma distributed remainder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShiftedParams<D> {
+    pub shift: f64,
+    pub distribution: Box<D>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Shifted<D> {
+    params: ShiftedParams<D>,
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> Shifted<D> {
+    pub fn new(distribution: D, shift: f64) -> Result<Self, DistrError> {
+        Self::try_from(ShiftedParams {
+            shift,
+            distribution: Box::new(distribution),
+        })
+    }
+    pub fn shift(&self) -> f64 {
+        self.params.shift
+    }
+    /// The distribution before shifting
+    pub fn inner(&self) -> &D {
+        &self.params.distribution
+    }
+}
+
+// Mirror trait methods for convenience
+impl<D: ContinuousUnivariate<f64, f64> + Distribution<f64>> Shifted<D> {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> TryFrom<ShiftedParams<D>> for Shifted<D> {
+    type Error = DistrError;
+
+    fn try_from(params: ShiftedParams<D>) -> Result<Self, Self::Error> {
+        if !params.shift.is_finite() {
+            return Err(DistrError(format!(
+                "Shift must be a finite number, got {}",
+                params.shift
+            )));
+        }
+        Ok(Self { params })
+    }
+}
+
+impl<D: std::fmt::Display> std::fmt::Display for Shifted<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Shifted({}, shift = {})",
+            self.params.distribution, self.params.shift
+        )
+    }
+}
+
+impl<D: Serialize> Serialize for Shifted<D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de, D> Deserialize<'de> for Shifted<D>
+where
+    D: Deserialize<'de> + ContinuousUnivariate<f64, f64>,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: serde::Deserializer<'de>,
+    {
+        let params = ShiftedParams::<D>::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> ContinuousUnivariate<f64, f64> for Shifted<D> {
+    fn pdf(&self, x: f64) -> f64 {
+        self.params.distribution.pdf(x - self.params.shift)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.params.distribution.ln_pdf(x - self.params.shift)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.params.distribution.cdf(x - self.params.shift)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.params.distribution.inverse_cdf(p) + self.params.shift
+    }
+    fn mean(&self) -> f64 {
+        self.params.distribution.mean() + self.params.shift
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // Each x is a draw y from the inner distribution plus the shift
+        let shift = self.params.shift;
+        let inner = &self.params.distribution;
+        let (lower, upper) = (lower - shift, upper - shift);
+        inner.partial_expectation(lower, upper) + shift * (inner.cdf(upper) - inner.cdf(lower))
+    }
+}
+
+impl<D: Distribution<f64>> Distribution<f64> for Shifted<D> {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.params.distribution.sample(rng) + self.params.shift
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ixa_plus::distr::{exponential::Exponential, gamma::Gamma, truncated::Truncated};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_shifted_gamma() {
+        let gamma = Gamma::from_shape_rate(3.0, 1.0).unwrap();
+        let d = Shifted::new(gamma.clone(), 1.0).unwrap();
+        assert_relative_eq!(d.mean(), 4.0, epsilon = 1e-12);
+        assert_relative_eq!(d.pdf(3.5), gamma.pdf(2.5), epsilon = 1e-12);
+        assert_relative_eq!(d.cdf(3.5), gamma.cdf(2.5), epsilon = 1e-12);
+        assert_relative_eq!(d.cdf(0.5), 0.0);
+        assert_relative_eq!(
+            d.inverse_cdf(0.5),
+            gamma.inverse_cdf(0.5) + 1.0,
+            epsilon = 1e-12
+        );
+
+        let n = 100_000;
+        let mut rng = rand::rng();
+        let samples: Vec<f64> = (0..n).map(|_| d.sample(&mut rng)).collect();
+        assert!(samples.iter().all(|x| *x >= 1.0));
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        assert_relative_eq!(mean, 4.0, epsilon = 0.05);
+    }
+
+    #[test]
+    fn test_truncated_shifted_mean() {
+        // Truncating a shifted exponential at the shift plus 1 leaves, by memorylessness,
+        // an exponential with the same mean starting there
+        let d = Truncated::new(
+            Shifted::new(Exponential::from_mean(2.0).unwrap(), 1.0).unwrap(),
+            Some(2.0),
+            None,
+        )
+        .unwrap();
+        assert_relative_eq!(d.mean(), 4.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_infinite_shift() {
+        let gamma = Gamma::from_shape_rate(3.0, 1.0).unwrap();
+        assert!(Shifted::new(gamma.clone(), f64::INFINITY).is_err());
+        assert!(Shifted::new(gamma, f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use serde::{Deserialize, Serialize};
+
+/// `distribution` restricted to `[lower, upper]`. Either bound may be left out, e.g.
+/// `{ lower = 1.0, distribution = { type = "gamma", shape = 3.0, rate = 1.0 } }` for a
+/// duration of at least one day.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TruncatedParams<D> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upper: Option<f64>,
+    pub distribution: Box<D>,
+}
+
+/// Bounds on a distribution, with what's needed to rescale its pdf, cdf and inverse cdf to
+/// them. Each method takes the matching function of the untruncated distribution. Both bounds
+/// are inclusive, so a point mass at either one is kept.
+#[derive(Debug, Clone, Copy)]
+pub struct Truncation {
+    lower: f64,
+    upper: f64,
+    // The largest value below `lower`. A cdf counts the mass up to and including its
+    // argument, so the mass kept is everything above this.
+    below: f64,
+    // The untruncated cdf at `below`, and the probability mass between the bounds
+    cdf_lower: f64,
+    mass: f64,
+}
+
+impl Truncation {
+    /// Missing bounds are infinite. `name` is the distribution, for errors.
+    pub fn new(
+        name: &str,
+        lower: Option<f64>,
+        upper: Option<f64>,
+        cdf: impl Fn(f64) -> f64,
+    ) -> Result<Self, DistrError> {
+        let lower = lower.unwrap_or(f64::NEG_INFINITY);
+        let upper = upper.unwrap_or(f64::INFINITY);
+        if lower.is_nan() || upper.is_nan() || lower >= upper {
+            return Err(DistrError(format!(
+                "{name} lower bound ({lower}) must be less than upper bound ({upper})"
+            )));
+        }
+        let below = lower.next_down();
+        let cdf_lower = if below.is_finite() { cdf(below) } else { 0.0 };
+        let cdf_upper = if upper.is_finite() { cdf(upper) } else { 1.0 };
+        let mass = cdf_upper - cdf_lower;
+        if mass.is_nan() || mass <= 0.0 {
+            return Err(DistrError(format!(
+                "{name} bounds [{lower}, {upper}] contain no probability mass"
+            )));
+        }
+        Ok(Self {
+            lower,
+            upper,
+            below,
+            cdf_lower,
+            mass,
+        })
+    }
+    pub fn lower(&self) -> f64 {
+        self.lower
+    }
+    pub fn upper(&self) -> f64 {
+        self.upper
+    }
+    pub fn is_truncated(&self) -> bool {
+        self.lower > f64::NEG_INFINITY || self.upper < f64::INFINITY
+    }
+    pub fn pdf(&self, x: f64, pdf: impl FnOnce(f64) -> f64) -> f64 {
+        if x < self.lower || x > self.upper {
+            return 0.0;
+        }
+        pdf(x) / self.mass
+    }
+    pub fn ln_pdf(&self, x: f64, ln_pdf: impl FnOnce(f64) -> f64) -> f64 {
+        if x < self.lower || x > self.upper {
+            return f64::NEG_INFINITY;
+        }
+        ln_pdf(x) - self.mass.ln()
+    }
+    pub fn cdf(&self, x: f64, cdf: impl FnOnce(f64) -> f64) -> f64 {
+        if x < self.lower {
+            return 0.0;
+        }
+        if x >= self.upper {
+            return 1.0;
+        }
+        (cdf(x) - self.cdf_lower) / self.mass
+    }
+    pub fn inverse_cdf(&self, p: f64, inverse_cdf: impl FnOnce(f64) -> f64) -> f64 {
+        inverse_cdf(self.cdf_lower + p * self.mass).clamp(self.lower, self.upper)
+    }
+    pub fn partial_expectation(
+        &self,
+        lower: f64,
+        upper: f64,
+        partial_expectation: impl FnOnce(f64, f64) -> f64,
+    ) -> f64 {
+        let (lower, upper) = (lower.max(self.below), upper.min(self.upper));
+        if lower >= upper {
+            return 0.0;
+        }
+        partial_expectation(lower, upper) / self.mass
+    }
+    pub fn mean(&self, partial_expectation: impl FnOnce(f64, f64) -> f64) -> f64 {
+        partial_expectation(self.below, self.upper) / self.mass
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Truncated<D> {
+    params: TruncatedParams<D>,
+    truncation: Truncation,
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> Truncated<D> {
+    pub fn new(
+        distribution: D,
+        lower: Option<f64>,
+        upper: Option<f64>,
+    ) -> Result<Self, DistrError> {
+        Self::try_from(TruncatedParams {
+            lower,
+            upper,
+            distribution: Box::new(distribution),
+        })
+    }
+    pub fn lower(&self) -> f64 {
+        self.truncation.lower()
+    }
+    pub fn upper(&self) -> f64 {
+        self.truncation.upper()
+    }
+    /// The distribution before truncation
+    pub fn inner(&self) -> &D {
+        &self.params.distribution
+    }
+}
+
+// Mirror trait methods for convenience
+impl<D: ContinuousUnivariate<f64, f64>> Truncated<D> {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> TryFrom<TruncatedParams<D>> for Truncated<D> {
+    type Error = DistrError;
+
+    fn try_from(params: TruncatedParams<D>) -> Result<Self, Self::Error> {
+        let truncation = Truncation::new("Truncation", params.lower, params.upper, |x| {
+            params.distribution.cdf(x)
+        })?;
+        Ok(Self { params, truncation })
+    }
+}
+
+impl<D: std::fmt::Display> std::fmt::Display for Truncated<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Truncated({}", self.params.distribution)?;
+        if let Some(lower) = self.params.lower {
+            write!(f, ", lower = {lower}")?;
+        }
+        if let Some(upper) = self.params.upper {
+            write!(f, ", upper = {upper}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<D: Serialize> Serialize for Truncated<D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de, D> Deserialize<'de> for Truncated<D>
+where
+    D: Deserialize<'de> + ContinuousUnivariate<f64, f64>,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: serde::Deserializer<'de>,
+    {
+        let params = TruncatedParams::<D>::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> ContinuousUnivariate<f64, f64> for Truncated<D> {
+    fn pdf(&self, x: f64) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation.pdf(x, |x| inner.pdf(x))
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation.ln_pdf(x, |x| inner.ln_pdf(x))
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation.cdf(x, |x| inner.cdf(x))
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation.inverse_cdf(p, |p| inner.inverse_cdf(p))
+    }
+    fn mean(&self) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation
+            .mean(|lower, upper| inner.partial_expectation(lower, upper))
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        let inner = &self.params.distribution;
+        self.truncation
+            .partial_expectation(lower, upper, |lower, upper| {
+                inner.partial_expectation(lower, upper)
+            })
+    }
+}
+
+impl<D: ContinuousUnivariate<f64, f64>> Distribution<f64> for Truncated<D> {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        // Inverse transform sampling keeps draws within the bounds without rejection
+        self.inverse_cdf(rng.random::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ixa_plus::distr::{
+        constant::Constant,
+        exponential::Exponential,
+        gamma::Gamma,
+        mixture::{Component, Mixture},
+        normal::Normal,
+    };
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_truncated_exponential() {
+        // An exponential truncated below is the same exponential shifted (memorylessness)
+        let d = Truncated::new(Exponential::from_mean(2.0).unwrap(), Some(1.0), None).unwrap();
+        assert_relative_eq!(d.mean(), 3.0, epsilon = 1e-12);
+        assert_relative_eq!(d.cdf(1.0), 0.0);
+        assert_relative_eq!(d.cdf(3.0), 1.0 - (-1.0f64).exp(), epsilon = 1e-12);
+        assert_relative_eq!(d.pdf(1.0), 0.5, epsilon = 1e-12);
+        assert_relative_eq!(d.pdf(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_truncated_mixture_mean() {
+        // Truncated below at 1, each exponential component keeps mass w * exp(-1 / m)
+        // and, by memorylessness, has mean 1 + m
+        let d = Truncated::new(
+            Mixture::new(vec![
+                Component {
+                    weight: 0.8,
+                    distribution: Exponential::from_mean(1.0).unwrap(),
+                },
+                Component {
+                    weight: 0.2,
+                    distribution: Exponential::from_mean(6.0).unwrap(),
+                },
+            ])
+            .unwrap(),
+            Some(1.0),
+            None,
+        )
+        .unwrap();
+        let masses = [0.8 * (-1.0f64).exp(), 0.2 * (-1.0 / 6.0f64).exp()];
+        let expected = (masses[0] * 2.0 + masses[1] * 7.0) / (masses[0] + masses[1]);
+        assert_relative_eq!(d.mean(), expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_truncated_gamma_mean() {
+        let gamma = Gamma::from_shape_rate(3.0, 1.0).unwrap();
+        let d = Truncated::new(gamma.clone(), Some(1.0), Some(5.0)).unwrap();
+        // Midpoint rule for the integral of x * pdf(x) over the bounds
+        let n = 100_000;
+        let width = 4.0 / n as f64;
+        let expected = (0..n)
+            .map(|i| 1.0 + (i as f64 + 0.5) * width)
+            .map(|x| x * gamma.pdf(x) * width)
+            .sum::<f64>()
+            / (gamma.cdf(5.0) - gamma.cdf(1.0));
+        assert_relative_eq!(d.mean(), expected, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_matches_truncated_normal() {
+        let normal = Normal::from_mean_sd(5.0, 3.0).unwrap();
+        let truncated = Truncated::new(normal, Some(1.0), Some(6.0)).unwrap();
+        let bounded = Normal::truncated(5.0, 3.0, 1.0, 6.0).unwrap();
+        assert_relative_eq!(truncated.mean(), bounded.mean(), epsilon = 1e-12);
+        assert_relative_eq!(truncated.cdf(4.0), bounded.cdf(4.0), epsilon = 1e-12);
+        assert_relative_eq!(
+            truncated.inverse_cdf(0.3),
+            bounded.inverse_cdf(0.3),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_samples_stay_in_bounds() {
+        let d = Truncated::new(
+            Gamma::from_shape_rate(3.0, 1.0).unwrap(),
+            Some(1.0),
+            Some(5.0),
+        )
+        .unwrap();
+        let mut rng = rand::rng();
+        for _ in 0..1000 {
+            let x = d.sample(&mut rng);
+            assert!((1.0..=5.0).contains(&x), "{x}");
+        }
+    }
+
+    #[test]
+    fn test_bounds_keep_point_masses() {
+        let constant = Constant::new(3.0).unwrap();
+        for (lower, upper) in [(Some(3.0), None), (None, Some(3.0)), (Some(3.0), Some(4.0))] {
+            let d = Truncated::new(constant.clone(), lower, upper).unwrap();
+            assert_eq!(d.mean(), 3.0);
+            assert_eq!(d.cdf(3.0), 1.0);
+            assert_eq!(d.inverse_cdf(0.5), 3.0);
+        }
+        assert!(Truncated::new(constant, Some(3.5), None).is_err());
+    }
+
+    #[test]
+    fn test_empty_bounds() {
+        let gamma = Gamma::from_shape_rate(3.0, 1.0).unwrap();
+        assert!(Truncated::new(gamma.clone(), Some(2.0), Some(1.0)).is_err());
+        assert!(Truncated::new(gamma, None, Some(-1.0)).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+pub use crate::uniform;
+use serde::{Deserialize, Serialize};
+use statrs::{
+    distribution::{self as sd, Continuous, ContinuousCDF},
+    statistics::{Distribution as StatisticsDistribution, Max, Min},
+};
+
+#[macro_export]
+macro_rules! uniform {
+    (min = $min:expr, max = $max:expr) => {
+        $crate::ixa_plus::distr::uniform::UniformParams::Range {
+            min: $min,
+            max: $max,
+        }
+        .try_into()
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum UniformParams {
+    Range { min: f64, max: f64 },
+}
+
+impl TryFrom<UniformParams> for sd::Uniform {
+    type Error = DistrError;
+    fn try_from(params: UniformParams) -> Result<sd::Uniform, DistrError> {
+        match params {
+            UniformParams::Range { min, max } => Ok(sd::Uniform::new(min, max)?),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Uniform {
+    params: UniformParams,
+    distr: sd::Uniform,
+}
+
+impl Uniform {
+    pub fn from_range(min: f64, max: f64) -> Result<Self, DistrError> {
+        Self::try_from(UniformParams::Range { min, max })
+    }
+    pub fn min(&self) -> f64 {
+        self.distr.min()
+    }
+    pub fn max(&self) -> f64 {
+        self.distr.max()
+    }
+}
+
+// Mirror trait methods for convenience
+impl Uniform {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<UniformParams> for Uniform {
+    type Error = DistrError;
+
+    fn try_from(params: UniformParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            params,
+            distr: params.try_into()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Uniform {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.params {
+            UniformParams::Range { min, max } => write!(f, "Uniform(min = {min}, max = {max})"),
+        }
+    }
+}
+
+impl Serialize for Uniform {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Uniform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = UniformParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Uniform {
+    fn pdf(&self, x: f64) -> f64 {
+        self.distr.pdf(x)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.distr.ln_pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        self.distr.mean().unwrap()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        let (min, max) = (self.min(), self.max());
+        let (lower, upper) = (lower.clamp(min, max), upper.clamp(min, max));
+        (upper.powi(2) - lower.powi(2)) / (2.0 * (max - min))
+    }
+}
+
+impl Distribution<f64> for Uniform {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_range() {
+        let d: Uniform = uniform!(min = 2.0, max = 6.0).unwrap();
+        assert_relative_eq!(d.mean(), 4.0);
+        assert_relative_eq!(d.cdf(3.0), 0.25);
+        assert_relative_eq!(d.inverse_cdf(0.75), 5.0);
+        let mut rng = rand::rng();
+        assert!((0..1000).all(|_| (2.0..6.0).contains(&d.sample(&mut rng))));
+
+        let result: Result<Uniform, _> = uniform!(min = 6.0, max = 2.0);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,249 @@
+pub use super::distribution::{ContinuousUnivariate, DistrError, Distribution};
+use super::fit::{Quantile, bisect, check_fit, check_quantiles, fit_line, format_quantiles};
+pub use crate::weibull;
+use serde::{Deserialize, Serialize};
+use statrs::{
+    distribution::{self as sd, Continuous, ContinuousCDF},
+    function::gamma::ln_gamma,
+    statistics::Distribution as StatisticsDistribution,
+};
+
+#[macro_export]
+macro_rules! weibull {
+    (shape = $shape:expr, scale = $scale:expr) => {
+        $crate::ixa_plus::distr::weibull::WeibullParams::Scale {
+            shape: $shape,
+            scale: $scale,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, sd = $sd:expr) => {
+        $crate::ixa_plus::distr::weibull::WeibullParams::MeanSd {
+            mean: $mean,
+            sd: $sd,
+        }
+        .try_into()
+    };
+    (mean = $mean:expr, cv = $cv:expr) => {
+        $crate::ixa_plus::distr::weibull::WeibullParams::MeanCv {
+            mean: $mean,
+            cv: $cv,
+        }
+        .try_into()
+    };
+}
+
+/// A Weibull distribution, given by shape and scale, by its mean and standard deviation
+/// or coefficient of variation, or fit to two or three quantiles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum WeibullParams {
+    Scale { shape: f64, scale: f64 },
+    MeanSd { mean: f64, sd: f64 },
+    MeanCv { mean: f64, cv: f64 },
+    Quantiles { quantiles: Vec<Quantile> },
+}
+
+impl WeibullParams {
+    /// The shape and scale this parameterization corresponds to
+    pub fn shape_scale(&self) -> Result<(f64, f64), DistrError> {
+        match *self {
+            WeibullParams::Scale { shape, scale } => Ok((shape, scale)),
+            WeibullParams::MeanSd { mean, sd } => {
+                if mean <= 0.0 || sd <= 0.0 {
+                    return Err(DistrError(
+                        "Weibull mean and sd must be greater than 0".to_string(),
+                    ));
+                }
+                shape_scale_from_mean_cv(mean, sd / mean)
+            }
+            WeibullParams::MeanCv { mean, cv } => {
+                if mean <= 0.0 || cv <= 0.0 {
+                    return Err(DistrError(
+                        "Weibull mean and cv must be greater than 0".to_string(),
+                    ));
+                }
+                shape_scale_from_mean_cv(mean, cv)
+            }
+            WeibullParams::Quantiles { ref quantiles } => {
+                check_quantiles("Weibull", quantiles, true)?;
+                // ln(x) = ln(scale) + ln(-ln(1 - p)) / shape
+                let points = quantiles
+                    .iter()
+                    .map(|q| ((-(1.0 - q.p).ln()).ln(), q.x.ln()))
+                    .collect::<Vec<_>>();
+                let (ln_scale, inv_shape) = fit_line(&points);
+                let (shape, scale) = (1.0 / inv_shape, ln_scale.exp());
+                let fitted = sd::Weibull::new(shape, scale)?;
+                check_fit("Weibull", quantiles, |p| fitted.inverse_cdf(p))?;
+                Ok((shape, scale))
+            }
+        }
+    }
+}
+
+// The coefficient of variation depends only on the shape, and decreases as it grows:
+// cv^2 + 1 = G(1 + 2 / shape) / G(1 + 1 / shape)^2
+fn shape_scale_from_mean_cv(mean: f64, cv: f64) -> Result<(f64, f64), DistrError> {
+    let target = (1.0 + cv.powi(2)).ln();
+    let ln_shape = bisect(
+        |ln_shape| {
+            let shape = ln_shape.exp();
+            target - (ln_gamma(1.0 + 2.0 / shape) - 2.0 * ln_gamma(1.0 + 1.0 / shape))
+        },
+        0.02f64.ln(),
+        1e3f64.ln(),
+    )
+    .ok_or_else(|| DistrError(format!("Weibull cv = {cv} is out of range")))?;
+    let shape = ln_shape.exp();
+    Ok((shape, mean / ln_gamma(1.0 + 1.0 / shape).exp()))
+}
+
+impl TryFrom<&WeibullParams> for sd::Weibull {
+    type Error = DistrError;
+    fn try_from(params: &WeibullParams) -> Result<sd::Weibull, DistrError> {
+        let (shape, scale) = params.shape_scale()?;
+        Ok(sd::Weibull::new(shape, scale)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Weibull {
+    params: WeibullParams,
+    distr: sd::Weibull,
+}
+
+impl Weibull {
+    pub fn from_shape_scale(shape: f64, scale: f64) -> Result<Self, DistrError> {
+        Self::try_from(WeibullParams::Scale { shape, scale })
+    }
+    pub fn from_mean_sd(mean: f64, sd: f64) -> Result<Self, DistrError> {
+        Self::try_from(WeibullParams::MeanSd { mean, sd })
+    }
+    pub fn from_mean_cv(mean: f64, cv: f64) -> Result<Self, DistrError> {
+        Self::try_from(WeibullParams::MeanCv { mean, cv })
+    }
+    pub fn from_quantiles(quantiles: &[Quantile]) -> Result<Self, DistrError> {
+        Self::try_from(WeibullParams::Quantiles {
+            quantiles: quantiles.to_vec(),
+        })
+    }
+    pub fn shape(&self) -> f64 {
+        self.distr.shape()
+    }
+    pub fn scale(&self) -> f64 {
+        self.distr.scale()
+    }
+}
+
+// Mirror trait methods for convenience
+impl Weibull {
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        <Self as Distribution<f64>>::sample(self, rng)
+    }
+    pub fn mean(&self) -> f64 {
+        <Self as ContinuousUnivariate<f64, f64>>::mean(self)
+    }
+}
+
+impl TryFrom<WeibullParams> for Weibull {
+    type Error = DistrError;
+
+    fn try_from(params: WeibullParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            distr: (&params).try_into()?,
+            params,
+        })
+    }
+}
+
+impl std::fmt::Display for Weibull {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.params {
+            WeibullParams::Scale { shape, scale } => {
+                write!(f, "Weibull(shape = {shape}, scale = {scale})")
+            }
+            WeibullParams::MeanSd { mean, sd } => write!(f, "Weibull(mean = {mean}, sd = {sd})"),
+            WeibullParams::MeanCv { mean, cv } => write!(f, "Weibull(mean = {mean}, cv = {cv})"),
+            WeibullParams::Quantiles { quantiles } => {
+                write!(f, "Weibull(quantiles = {})", format_quantiles(quantiles))
+            }
+        }
+    }
+}
+
+impl Serialize for Weibull {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.params.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Weibull {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = WeibullParams::deserialize(deserializer)?;
+        params.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ContinuousUnivariate<f64, f64> for Weibull {
+    fn pdf(&self, x: f64) -> f64 {
+        self.distr.pdf(x)
+    }
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.distr.ln_pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distr.cdf(x)
+    }
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.distr.inverse_cdf(p)
+    }
+    fn mean(&self) -> f64 {
+        self.distr.mean().unwrap()
+    }
+    fn partial_expectation(&self, lower: f64, upper: f64) -> f64 {
+        // Substituting t = (x / scale)^shape turns the integral into the mean times a
+        // Gamma(1 + 1 / shape, 1) cdf
+        let (shape, scale) = (self.distr.shape(), self.distr.scale());
+        let gamma = sd::Gamma::new(1.0 + 1.0 / shape, 1.0).unwrap();
+        let t = |x: f64| (x.max(0.0) / scale).powf(shape);
+        self.mean() * (gamma.cdf(t(upper)) - gamma.cdf(t(lower)))
+    }
+}
+
+impl Distribution<f64> for Weibull {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.distr.sample(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mean_sd_matches_moments() {
+        let d: Weibull = weibull!(mean = 5.2, sd = 1.5).unwrap();
+        assert_relative_eq!(d.mean(), 5.2, epsilon = 1e-9);
+        assert_relative_eq!(d.distr.std_dev().unwrap(), 1.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_fit_quantiles() {
+        let truth = Weibull::from_shape_scale(2.5, 6.0).unwrap();
+        let quantiles = [0.1, 0.9].map(|p| Quantile {
+            p,
+            x: truth.inverse_cdf(p),
+        });
+        let d = Weibull::from_quantiles(&quantiles).unwrap();
+        assert_relative_eq!(d.shape(), 2.5, epsilon = 1e-9);
+        assert_relative_eq!(d.scale(), 6.0, epsilon = 1e-9);
+    }
+}
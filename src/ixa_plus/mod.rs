@@ -1,5 +1,6 @@
 pub mod distr;
 pub mod log;
+pub mod params_file;
 pub mod params_macro;
 pub mod rate_fn;
 pub mod type_index;
@@ -0,0 +1,220 @@
+use super::params_macro::{IxaParameters, IxaParametersBuilder};
+use anyhow::{Result, anyhow, bail};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::{Path, PathBuf};
+
+/// The file formats parameters can be read from and written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamsFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ParamsFormat {
+    /// Picks a format based on the file extension of `path`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("toml") => Ok(ParamsFormat::Toml),
+            Some("json") => Ok(ParamsFormat::Json),
+            Some("yaml" | "yml") => Ok(ParamsFormat::Yaml),
+            _ => bail!(
+                "{}: Unsupported config file format. Use .toml, .json, .yaml or .yml",
+                path.display()
+            ),
+        }
+    }
+
+    /// Parses `contents`, reporting errors as `<path>:<line>:<column>: <message>`
+    pub fn parse<T: DeserializeOwned, P: AsRef<Path>>(&self, contents: &str, path: P) -> Result<T> {
+        let (message, location) = match self {
+            ParamsFormat::Toml => match toml::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(e) => (
+                    e.message().to_string(),
+                    e.span().map(|span| line_column(contents, span.start)),
+                ),
+            },
+            ParamsFormat::Json => match serde_json::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let location = (e.line() > 0).then(|| (e.line(), e.column()));
+                    (strip_location(e.to_string(), location), location)
+                }
+            },
+            ParamsFormat::Yaml => match serde_yaml::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let location = e.location().map(|l| (l.line(), l.column()));
+                    (strip_location(e.to_string(), location), location)
+                }
+            },
+        };
+        let path = path.as_ref().display();
+        Err(match location {
+            Some((line, column)) => anyhow!("{path}:{line}:{column}: {message}"),
+            None => anyhow!("{path}: {message}"),
+        })
+    }
+
+    /// Serializes `value` in this format
+    pub fn to_string<T: Serialize>(&self, value: &T) -> Result<String> {
+        Ok(match self {
+            ParamsFormat::Toml => toml::to_string(value)?,
+            ParamsFormat::Json => serde_json::to_string_pretty(value)?,
+            ParamsFormat::Yaml => serde_yaml::to_string(value)?,
+        })
+    }
+}
+
+/// Converts a byte offset into a 1-based line and column
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// serde_json and serde_yaml append " at line X column Y" to their messages; drop it
+/// since we report the location ourselves.
+fn strip_location(message: String, location: Option<(usize, usize)>) -> String {
+    match location {
+        Some((line, column)) => message
+            .strip_suffix(&format!(" at line {line} column {column}"))
+            .map(str::to_string)
+            .unwrap_or(message),
+        None => message,
+    }
+}
+
+/// The optional `extends` key of a parameter file, which lists other parameter files
+/// (relative to this one) that it layers on top of.
+#[derive(Deserialize)]
+struct Extends {
+    #[serde(default)]
+    extends: ExtendsPaths,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExtendsPaths {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+impl Default for ExtendsPaths {
+    fn default() -> Self {
+        ExtendsPaths::Many(Vec::new())
+    }
+}
+
+impl ExtendsPaths {
+    fn into_vec(self) -> Vec<PathBuf> {
+        match self {
+            ExtendsPaths::One(path) => vec![path],
+            ExtendsPaths::Many(paths) => paths,
+        }
+    }
+}
+
+/// Reads a parameter file along with everything it `extends`. Later entries in `extends`
+/// override earlier ones, and the file itself overrides all of them. Returns the merged
+/// builder and the files in the order they were applied.
+pub fn read_layers<P: IxaParameters>(path: &Path) -> Result<(P::Builder, Vec<PathBuf>)> {
+    let mut order = Vec::new();
+    let builder = read_layer::<P>(path, &mut Vec::new(), &mut order)?;
+    Ok((builder, order))
+}
+
+fn read_layer<P: IxaParameters>(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    order: &mut Vec<PathBuf>,
+) -> Result<P::Builder> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    if let Some(start) = stack.iter().position(|p| p == &canonical) {
+        let cycle = stack[start..]
+            .iter()
+            .chain([&canonical])
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        bail!("Parameter files extend each other in a cycle: {cycle}");
+    }
+
+    let format = ParamsFormat::from_path(path)?;
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    let Extends { extends } = format.parse(&contents, path)?;
+    let file_params: P::Builder = format.parse(&contents, path)?;
+
+    // Relative paths are resolved against the directory of the including file
+    let dir = canonical
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    stack.push(canonical.clone());
+    let mut base: Option<P::Builder> = None;
+    for parent in extends.into_vec() {
+        let layer = read_layer::<P>(&dir.join(parent), stack, order)?;
+        base = Some(match base {
+            Some(base) => layer.extend_from(base),
+            None => layer,
+        });
+    }
+    stack.pop();
+    order.push(canonical);
+
+    Ok(match base {
+        Some(base) => file_params.extend_from(base),
+        None => file_params,
+    })
+}
+
+/// Parses command-line overrides of the form `key=value` into a builder. Keys are dotted
+/// paths such as `population.size`, and values are read as TOML, falling back to a plain
+/// string so that e.g. `output.dir=runs/a` needs no quotes.
+pub fn parse_overrides<P: IxaParameters>(overrides: &[String]) -> Result<P::Builder> {
+    let known = toml::Value::try_from(P::Builder::default())?;
+    let mut table = toml::Table::new();
+    for assignment in overrides {
+        let Some((key, value)) = assignment.split_once('=') else {
+            bail!("Expected a parameter override as key=value, got {assignment}");
+        };
+        let key = key.trim();
+        let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+            .ok()
+            .and_then(|mut parsed| parsed.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+
+        let path: Vec<&str> = key.split('.').collect();
+        let (last, sections) = path.split_last().unwrap();
+        let mut current = &mut table;
+        for section in sections {
+            let entry = current
+                .entry(section.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let toml::Value::Table(inner) = entry else {
+                bail!("Can't override {key}: {section} is not a section");
+            };
+            current = inner;
+        }
+        current.insert(last.to_string(), value);
+
+        // Unknown keys would otherwise be silently ignored. Optional parameters are left
+        // out of the defaults, so only warn.
+        if path
+            .iter()
+            .try_fold(&known, |value, part| value.get(part))
+            .is_none()
+        {
+            log::warn!("Overriding {key}, which isn't one of the default parameters");
+        }
+    }
+    toml::Value::Table(table)
+        .try_into()
+        .map_err(|e: toml::de::Error| anyhow!("In parameter overrides: {}", e.message()))
+}
@@ -1,17 +1,39 @@
+use super::params_file::{ParamsFormat, parse_overrides, read_layers};
+
 #[macro_export]
 macro_rules! define_parameters {
+    // A field given a `layer` type is held in the builder as that type, so files and
+    // overrides are merged into it key by key rather than replacing the whole value.
+    (@layer_type $field_type:ty) => { $field_type };
+    (@layer_type $field_type:ty, $layer_type:ty) => { $layer_type };
+    (@merge $value:expr, $other:expr) => { $value.or($other) };
+    (@merge $value:expr, $other:expr, $layer_type:ty) => {
+        match ($value, $other) {
+            (Some(value), Some(other)) => Some(<$layer_type>::extend_from(value, other)),
+            (value, other) => value.or(other),
+        }
+    };
+    // Generates a parameter struct along with its builder, validation and layering logic.
+    // This is used both for the top-level parameters and for each nested group.
     (
-        defaults: $default_file:expr,
+        @struct
         $(#[$meta:meta])*
         $vis:vis struct $name:ident {
             $(
                 $(#[$field_meta:meta])*
                 $field_name:ident : $field_type:ty $({
+                    $( layer : $layer_type:ty, )?
                     $( default : $default_value:expr, )?
                     $( validate($validate_arg:ident) $validate_body:block )?
                 })?
             ),* $(,)?
         }
+        groups {
+            $(
+                $(#[$group_meta:meta])*
+                $group_name:ident : $group_type:ident
+            ),* $(,)?
+        }
     ) => {
         $(#[$meta])*
         #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -20,69 +42,86 @@ macro_rules! define_parameters {
                 $(#[$field_meta])*
                 pub $field_name: $field_type,
             )*
-        }
-
-
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                let json = toml::to_string(&self).unwrap();
-                write!(f, "{}", json)
-            }
+            $(
+                $(#[$group_meta])*
+                pub $group_name: $group_type,
+            )*
         }
 
         paste::paste! {
-            impl $name {
-                pub fn builder() -> [<$name Builder>] {
-                    [<$name Builder>]::default()
-                }
-            }
-            static DEFAULT_PARAMS: LazyLock<[<$name Builder>]> = LazyLock::new(|| {
-                toml::from_str::<[<$name Builder>]>(include_str!($default_file))
-                    .expect("Failed to parse default parameters")
-            });
-
-            impl Default for $name {
-                fn default() -> Self {
-                    [<$name Builder>]::default().try_into().unwrap()
-                }
-            }
-
             #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
             pub struct [<$name Builder>] {
                 $(
                     $(#[$field_meta])*
-                    $field_name: Option<$field_type>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    $field_name: Option<$crate::define_parameters!(@layer_type $field_type $($(, $layer_type)?)?)>,
+                )*
+                $(
+                    $(#[$group_meta])*
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    $group_name: Option<[<$group_type Builder>]>,
                 )*
             }
 
-
-            impl $crate::ixa_plus::params_macro::IxaParameters for $name {
-                type Builder = [<$name Builder>];
-            }
-
-            impl $crate::ixa_plus::params_macro::IxaParametersBuilder<$name> for [<$name Builder>] {
-                fn extend_from(self, other: Self) -> Self {
+            impl From<$name> for [<$name Builder>] {
+                fn from(params: $name) -> Self {
                     Self {
-                        $(
-                            $field_name: self.$field_name.or(other.$field_name),
-                        )*
+                        $( $field_name: Some(params.$field_name.into()), )*
+                        $( $group_name: Some(params.$group_name.into()), )*
                     }
                 }
-                fn build(self) -> Result<$name, anyhow::Error> {
-                    self.build()
-                }
             }
 
             impl [<$name Builder>] {
+                /// A builder with no values set, not even defaults
+                #[allow(dead_code)]
+                fn empty() -> Self {
+                    Self {
+                        $( $field_name: None, )*
+                        $( $group_name: None, )*
+                    }
+                }
+
                 $(
                     #[inline]
                     #[allow(dead_code)]
                     pub fn $field_name(mut self, value: $field_type) -> Self {
-                        self.$field_name = Some(value);
+                        self.$field_name = Some(value.into());
                         self
                     }
                 )*
 
+                $(
+                    /// Sets values in a nested group; any values not set in `value`
+                    /// keep whatever this builder already had.
+                    #[inline]
+                    #[allow(dead_code)]
+                    pub fn $group_name(mut self, value: [<$group_type Builder>]) -> Self {
+                        self.$group_name = Some(match self.$group_name.take() {
+                            Some(existing) => value.extend_from(existing),
+                            None => value,
+                        });
+                        self
+                    }
+                )*
+
+                /// Fills in any values missing from `self` with values from `other`,
+                /// recursing into nested groups.
+                pub fn extend_from(self, other: Self) -> Self {
+                    Self {
+                        $(
+                            $field_name: $crate::define_parameters!(
+                                @merge self.$field_name, other.$field_name $($(, $layer_type)?)?
+                            ),
+                        )*
+                        $(
+                            $group_name: match (self.$group_name, other.$group_name) {
+                                (Some(value), Some(other)) => Some(value.extend_from(other)),
+                                (value, other) => value.or(other),
+                            },
+                        )*
+                    }
+                }
 
                 $(
 
@@ -102,8 +141,11 @@ macro_rules! define_parameters {
                 $(
                     #[inline]
                     #[allow(unreachable_code)]
-                    fn [<build_ $field_name>](value: Option<$field_type>) -> Result<$field_type, anyhow::Error> {
+                    fn [<build_ $field_name>](
+                        value: Option<$crate::define_parameters!(@layer_type $field_type $($(, $layer_type)?)?)>,
+                    ) -> Result<$field_type, anyhow::Error> {
                         if let Some(value) = value {
+                            let value: $field_type = value.into();
                             Self::[<validate_ $field_name>](&value)
                                 .map_err(|e| anyhow::anyhow!(
                                     concat!("Validation failed for parameter ", stringify!($field_name), ": ",)
@@ -120,19 +162,38 @@ macro_rules! define_parameters {
                         Err(anyhow::anyhow!(concat!("Missing value for parameter ", stringify!($field_name)).to_string()))
                     }
                 )*
+
+                $(
+                    #[inline]
+                    fn [<build_ $group_name>](value: Option<[<$group_type Builder>]>) -> Result<$group_type, anyhow::Error> {
+                        value
+                            .unwrap_or_else([<$group_type Builder>]::empty)
+                            .build_unwrapped()
+                            .map_err(|e| anyhow::anyhow!(
+                                concat!("In parameter group ", stringify!($group_name), ": ")
+                                    .to_string() + &e.to_string()
+                            ))
+                    }
+                )*
+
                 fn build(self) -> Result<$name, anyhow::Error> {
+                    self.build_unwrapped()
+                        .map_err(|e| $crate::IxaError::IxaError(e.to_string()).into())
+                }
+
+                // Errors from nested groups are wrapped in `IxaError` once, by whichever
+                // builder `build` was called on, rather than at every level
+                fn build_unwrapped(self) -> Result<$name, anyhow::Error> {
                     Ok($name {
                         $(
-                            $field_name: Self::[<build_ $field_name>](self.$field_name).map_err(|e| $crate::IxaError::IxaError(e.to_string()))?,
+                            $field_name: Self::[<build_ $field_name>](self.$field_name)?,
+                        )*
+                        $(
+                            $group_name: Self::[<build_ $group_name>](self.$group_name)?,
                         )*
                     })
                 }
             }
-            impl Default for [<$name Builder>] {
-                fn default() -> Self {
-                    DEFAULT_PARAMS.clone()
-                }
-            }
 
             impl TryFrom<[<$name Builder>]> for $name {
                 type Error = anyhow::Error;
@@ -140,6 +201,131 @@ macro_rules! define_parameters {
                     builder.build()
                 }
             }
+        }
+    };
+    // The public form: top-level fields, then any number of `[group] struct` sections.
+    // Groups only go one level deep. Each is expanded with no groups of its own, and the
+    // `param_<group>_<field>` accessors on `ParametersExt` assume that, so a group can't
+    // contain another group.
+    (
+        defaults: $default_file:expr,
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_name:ident : $field_type:ty $({
+                    $( layer : $layer_type:ty, )?
+                    $( default : $default_value:expr, )?
+                    $( validate($validate_arg:ident) $validate_body:block )?
+                })?
+            ),* $(,)?
+        }
+        $(
+            $(#[$group_meta:meta])*
+            [$group_name:ident]
+            $group_vis:vis struct $group_type:ident {
+                $(
+                    $(#[$group_field_meta:meta])*
+                    $group_field_name:ident : $group_field_type:ty $({
+                        $( layer : $group_layer_type:ty, )?
+                        $( default : $group_default_value:expr, )?
+                        $( validate($group_validate_arg:ident) $group_validate_body:block )?
+                    })?
+                ),* $(,)?
+            }
+        )*
+    ) => {
+        $crate::define_parameters! {
+            @struct
+            $(#[$meta])*
+            $vis struct $name {
+                $(
+                    $(#[$field_meta])*
+                    $field_name : $field_type $({
+                        $( layer : $layer_type, )?
+                        $( default : $default_value, )?
+                        $( validate($validate_arg) $validate_body )?
+                    })?
+                ),*
+            }
+            groups {
+                $(
+                    $(#[$group_meta])*
+                    $group_name : $group_type
+                ),*
+            }
+        }
+
+        $(
+            $crate::define_parameters! {
+                @struct
+                $(#[$group_meta])*
+                $group_vis struct $group_type {
+                    $(
+                        $(#[$group_field_meta])*
+                        $group_field_name : $group_field_type $({
+                            $( layer : $group_layer_type, )?
+                            $( default : $group_default_value, )?
+                            $( validate($group_validate_arg) $group_validate_body )?
+                        })?
+                    ),*
+                }
+                groups {}
+            }
+        )*
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let json = toml::to_string(&self).unwrap();
+                write!(f, "{}", json)
+            }
+        }
+
+        paste::paste! {
+            impl $name {
+                pub fn builder() -> [<$name Builder>] {
+                    [<$name Builder>]::default()
+                }
+            }
+            $(
+                impl $group_type {
+                    /// An empty builder for this group; unset values fall back to the
+                    /// defaults when merged into the top-level builder.
+                    #[allow(dead_code)]
+                    pub fn builder() -> [<$group_type Builder>] {
+                        [<$group_type Builder>]::empty()
+                    }
+                }
+            )*
+            static DEFAULT_PARAMS: LazyLock<[<$name Builder>]> = LazyLock::new(|| {
+                toml::from_str::<[<$name Builder>]>(include_str!($default_file))
+                    .expect("Failed to parse default parameters")
+            });
+
+            impl Default for $name {
+                fn default() -> Self {
+                    [<$name Builder>]::default().try_into().unwrap()
+                }
+            }
+
+            impl $crate::ixa_plus::params_macro::IxaParameters for $name {
+                type Builder = [<$name Builder>];
+            }
+
+            impl $crate::ixa_plus::params_macro::IxaParametersBuilder<$name> for [<$name Builder>] {
+                fn extend_from(self, other: Self) -> Self {
+                    Self::extend_from(self, other)
+                }
+                fn build(self) -> Result<$name, anyhow::Error> {
+                    self.build()
+                }
+            }
+
+            impl Default for [<$name Builder>] {
+                fn default() -> Self {
+                    DEFAULT_PARAMS.clone()
+                }
+            }
 
             ixa::define_global_property!(GlobalParams, $name);
 
@@ -163,6 +349,20 @@ macro_rules! define_parameters {
                         &self.params().$field_name
                     }
                 )*
+                $(
+                    #[inline]
+                    #[allow(dead_code)]
+                    fn [<param_ $group_name>](&self) -> &$group_type {
+                        &self.params().$group_name
+                    }
+                    $(
+                        #[inline]
+                        #[allow(dead_code)]
+                        fn [<param_ $group_name _ $group_field_name>](&self) -> &$group_field_type {
+                            &self.params().$group_name.$group_field_name
+                        }
+                    )*
+                )*
             }
             impl<C> ParametersExt for C where C: PluginContext {}
         }
@@ -170,51 +370,87 @@ macro_rules! define_parameters {
 }
 
 pub trait IxaParametersBuilder<P: IxaParameters>:
-    Sized + serde::de::DeserializeOwned + Default
+    Sized + serde::Serialize + serde::de::DeserializeOwned + Default + From<P>
 {
     fn extend_from(self, other: Self) -> Self;
     fn build(self) -> Result<P, anyhow::Error>;
 }
 
-pub trait IxaParameters: Sized + serde::Serialize + serde::de::DeserializeOwned {
+pub trait IxaParameters: Sized + Clone + serde::Serialize + serde::de::DeserializeOwned {
     type Builder: IxaParametersBuilder<Self>;
     fn builder() -> Self::Builder {
         Self::Builder::default()
     }
     fn from_args() -> Option<Self> {
-        let args = std::env::args().collect::<Vec<_>>();
-        // File --params <path>
-        let mut prev_arg: Option<&str> = None;
-        for arg in &args[1..] {
-            if let Some(prev) = prev_arg {
-                if prev == "--params" {
-                    return Some(
-                        Self::try_from_file(arg).expect("Could not parse parameters from file"),
-                    );
-                }
-            }
-            prev_arg = Some(arg);
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        Self::try_from_args(&args).expect("Could not parse parameters")
+    }
+    /// Reads `--params <path>` and any number of `--set key=value` overrides, which are
+    /// applied on top of the file. Returns None if neither is given.
+    fn try_from_args(args: &[String]) -> anyhow::Result<Option<Self>> {
+        let mut path = None;
+        let mut overrides = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--params" => {
+                    path = Some(
+                        args.next()
+                            .ok_or_else(|| anyhow::anyhow!("--params needs a file path"))?,
+                    )
+                }
+                "--set" => overrides.push(
+                    args.next()
+                        .ok_or_else(|| anyhow::anyhow!("--set needs a key=value"))?
+                        .clone(),
+                ),
+                _ => {}
+            }
+        }
+        if path.is_none() && overrides.is_empty() {
+            return Ok(None);
+        }
+
+        let params = match path {
+            Some(path) => Self::builder_from_file(path)?,
+            None => Self::Builder::default(),
+        };
+        if overrides.is_empty() {
+            return Ok(Some(params.build()?));
         }
-        None
+        log::info!("Overriding parameters: {}", overrides.join(", "));
+        let overrides = parse_overrides::<Self>(&overrides)?;
+        Ok(Some(overrides.extend_from(params).build()?))
     }
-    // Parse parameters from toml or json
+    // Parse parameters from toml, json or yaml, including any files listed under `extends`
     fn try_from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
-        let contents = std::fs::read_to_string(&path)
-            .map_err(|e| anyhow::anyhow!("{}: {}", path.as_ref().display(), e))?;
-
-        let file_params: Self::Builder =
-            if path.as_ref().extension().and_then(|s| s.to_str()) == Some("json") {
-                serde_json::from_str(&contents)?
-            } else if path.as_ref().extension().and_then(|s| s.to_str()) == Some("toml") {
-                log::info!("Loading parameters from file {}", path.as_ref().display());
-                toml::from_str(&contents)?
-            } else {
-                anyhow::bail!("Unsupported config file format. Use .toml or .json");
-            };
+        Self::builder_from_file(path)?.build()
+    }
+    /// Reads a parameter file and everything it extends, on top of the defaults
+    fn builder_from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self::Builder> {
+        log::info!("Loading parameters from file {}", path.as_ref().display());
+        let (file_params, order) = read_layers::<Self>(path.as_ref())?;
+        log::info!(
+            "Parameter merge order: defaults -> {}",
+            order
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        );
 
         // File params should extend default params
-        let params = file_params.extend_from(Self::Builder::default());
-
-        Ok(params.build()?)
+        Ok(file_params.extend_from(Self::Builder::default()))
+    }
+    /// Serializes the fully resolved parameters in the given format. This goes through
+    /// the builder so the output can be read back with `try_from_file`.
+    fn to_string_as(&self, format: ParamsFormat) -> anyhow::Result<String> {
+        format.to_string(&Self::Builder::from(self.clone()))
+    }
+    /// Writes the fully resolved parameters to `path`, in the format given by its extension
+    fn write_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> anyhow::Result<()> {
+        let contents = self.to_string_as(ParamsFormat::from_path(&path)?)?;
+        std::fs::write(&path, contents)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.as_ref().display(), e))
     }
 }
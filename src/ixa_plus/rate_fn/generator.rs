@@ -45,6 +45,13 @@ impl RateFnDataContainer {
         let index = self.per_person_rates.get::<T>()?.get(&person_id)?;
         self.rate_instances.get(*index)
     }
+    pub fn get_rate_fns<T: TypeIndex<RateFn>>(&self) -> impl Iterator<Item = &RateFn> {
+        self.per_person_rates
+            .get::<T>()
+            .into_iter()
+            .flat_map(|people| people.values())
+            .map(|index| &self.rate_instances[*index])
+    }
 }
 
 define_data_plugin!(RateFnPlugin, RateFnDataContainer, |_context| {
@@ -72,6 +79,13 @@ pub trait RateFnExt: PluginContext {
             .expect("Rate function not found");
         rate_fn
     }
+    /// Every rate function `generator` has assigned so far, in no particular order
+    fn get_rate_fns<G: RateFnGenerator<Self> + TypeIndex<RateFn>>(
+        &self,
+        _generator: G,
+    ) -> impl Iterator<Item = &RateFn> {
+        self.get_data(RateFnPlugin).get_rate_fns::<G>()
+    }
 }
 
 impl<T> RateFnExt for T where T: PluginContext {}
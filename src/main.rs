@@ -1,13 +1,22 @@
+mod compression;
+mod event_output;
+mod generation_intervals;
+mod incidence;
 mod infection_manager;
 mod infection_status;
 pub mod ixa_plus;
+mod manifest;
 mod model;
 mod output_manager;
 mod params;
 mod population_manager;
+mod report;
 mod simulation_event;
+mod summary;
 mod total_infectiousness_multiplier;
 mod transmission_manager;
+mod transmission_tree;
+mod websocket;
 
 // Helper for importing all extensions
 // use crate::ext::*;
@@ -20,7 +29,6 @@ pub mod ext {
 }
 
 use crate::ixa_plus::params_macro::IxaParameters;
-use crate::output_manager::OutputManagerExt;
 use ixa::prelude::*;
 
 fn main() {
@@ -33,7 +41,14 @@ fn main() {
 
     // Use mise run --params <file> to override default parameters
     let params = params::Params::from_args();
+
+    // Use mise run websocket, or --websocket <address>, to run for a WebSocket client
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(address) = websocket::address_from_args(&args) {
+        websocket::serve(&address, params.unwrap_or_default()).unwrap();
+        return;
+    }
+
     let mut context = model::setup(params).unwrap();
-    context.execute();
-    context.log_stats();
+    output_manager::execute(&mut context);
 }
@@ -0,0 +1,90 @@
+use crate::params::Params;
+use anyhow::Result;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// What produced a run's output: the resolved parameters, the code version, timings, and a
+/// checksum of every other output file so results can be checked against it later. A run
+/// that failed part way has `completed` false and the `error` that stopped it.
+#[derive(Serialize)]
+pub struct Manifest<'a> {
+    pub completed: bool,
+    pub error: Option<String>,
+    pub params: &'a Params,
+    pub seed: u64,
+    pub started: String,
+    pub finished: String,
+    pub wall_time_seconds: f64,
+    pub simulated_end_time: f64,
+    pub events: usize,
+    pub version: &'static str,
+    pub git_commit: Option<&'static str>,
+    /// SHA-256 of each output file, by file name
+    pub checksums: BTreeMap<String, String>,
+}
+
+impl<'a> Manifest<'a> {
+    pub fn new(
+        params: &'a Params,
+        started: SystemTime,
+        simulated_end_time: f64,
+        events: usize,
+        files: &[impl AsRef<Path>],
+        error: Option<String>,
+    ) -> Result<Self> {
+        let finished = SystemTime::now();
+        let mut checksums = BTreeMap::new();
+        for path in files {
+            let path = path.as_ref();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            checksums.insert(name, sha256(path)?);
+        }
+        Ok(Self {
+            completed: error.is_none(),
+            error,
+            params,
+            seed: params.seed,
+            started: humantime::format_rfc3339_seconds(started).to_string(),
+            finished: humantime::format_rfc3339_seconds(finished).to_string(),
+            wall_time_seconds: finished
+                .duration_since(started)
+                .unwrap_or_default()
+                .as_secs_f64(),
+            simulated_end_time,
+            events,
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: option_env!("GIT_COMMIT"),
+            checksums,
+        })
+    }
+}
+
+pub fn sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sha256_of_file() {
+        let path = std::env::temp_dir().join(format!("manifest_{}.txt", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
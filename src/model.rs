@@ -1,8 +1,20 @@
-use crate::{ext::*, params::Params};
+use crate::{
+    ext::*,
+    params::{Params, PopulationParams},
+};
 use anyhow::Result;
 use ixa::prelude::*;
 
 pub fn setup(params_override: Option<Params>) -> Result<Context> {
+    setup_with(params_override, |_| {})
+}
+
+/// Sets up the model like `setup`, calling `configure` once parameters and output are in
+/// place but before the population is created, so it sees the initial infections
+pub fn setup_with(
+    params_override: Option<Params>,
+    configure: impl FnOnce(&mut Context),
+) -> Result<Context> {
     let mut context = Context::new();
 
     let params = if let Some(override_params) = params_override {
@@ -17,9 +29,13 @@ pub fn setup(params_override: Option<Params>) -> Result<Context> {
     let &Params {
         max_time,
         seed,
-        population_size,
-        p_initial_recovered,
-        p_initial_incidence,
+        population:
+            PopulationParams {
+                size: population_size,
+                p_initial_recovered,
+                p_initial_incidence,
+                ..
+            },
         ..
     } = params;
 
@@ -28,6 +44,7 @@ pub fn setup(params_override: Option<Params>) -> Result<Context> {
 
     // Initialize output capture before creating the population so we don't miss creation events.
     context.capture_output();
+    configure(&mut context);
 
     // Add a plan to shut down the simulation after `max_time`, regardless of
     // what else is happening in the model.
@@ -59,5 +76,8 @@ pub fn setup(params_override: Option<Params>) -> Result<Context> {
         ],
     )?;
 
+    // Cases imported from outside the population, if any, arrive once a day
+    context.schedule_imported_cases();
+
     Ok(context)
 }
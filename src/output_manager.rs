@@ -1,28 +1,130 @@
+use crate::compression::{Compression, CsvWriter, OutputFile};
+use crate::event_output::EventWriters;
 use crate::ext::ParametersExt;
+use crate::generation_intervals::GenerationIntervalWriter;
+use crate::incidence::{DailyIncidenceWriter, IncidenceStratum, StratumFn};
+use crate::infection_manager::InfectionRate;
 use crate::infection_status::*;
+use crate::ixa_plus::distr::{
+    copula::{GaussianCopula, rank_correlation},
+    negative_binomial::NegativeBinomial,
+};
+use crate::ixa_plus::rate_fn::{InfectiousnessRateFn, RateFnExt};
+use crate::manifest::Manifest;
+use crate::params::OutputParams;
+use crate::report::Report;
 use crate::simulation_event::SimulationEvent;
+use crate::summary::Summary;
+use crate::transmission_tree::TransmissionTreeWriter;
 use anyhow::Result;
-use ixa::{PersonPropertyChangeEvent, prelude::*};
-use std::io::BufWriter;
+use ixa::{HashMap, PersonPropertyChangeEvent, prelude::*};
+use std::cell::RefCell;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Once;
+use std::time::SystemTime;
 
-const OUTPUT_DIR: &str = "output";
+const MANIFEST_FILE: &str = "manifest.json";
 
-fn create_output_file(filename: &str) -> Result<std::fs::File> {
-    std::fs::create_dir_all(OUTPUT_DIR)
-        .and_then(|_| {
-            let mut path = PathBuf::from(OUTPUT_DIR);
-            path.push(filename);
-            std::fs::File::create(path)
-        })
-        .map_err(|e| anyhow::anyhow!("Failed to create output file: {}", e))
+// Kept apart from the model's generators so sampling events doesn't change the simulation
+define_rng!(EventSampleRng);
+
+// The panic hook is shared by the whole process, so it's only installed once
+static PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    // Where and why a run on this thread panicked, recorded by the hook set in `execute`
+    // so the manifest can say
+    static PANIC_MESSAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Where output files go and what they're called, resolved from the `[output]` parameters
+pub struct OutputFiles {
+    dir: PathBuf,
+    prefix: String,
+    overwrite: bool,
+    compression: Compression,
+    // Every file created so far, so the manifest can list them
+    created: Vec<PathBuf>,
+}
+
+impl OutputFiles {
+    /// Fills in `{seed}` and `{scenario}` in the directory and prefix
+    pub fn new(params: &OutputParams, seed: u64) -> Self {
+        let fill = |template: &str| {
+            template
+                .replace("{seed}", &seed.to_string())
+                .replace("{scenario}", &params.scenario)
+        };
+        Self {
+            dir: PathBuf::from(fill(&params.dir)),
+            prefix: fill(&params.prefix),
+            overwrite: params.overwrite,
+            compression: params.compression,
+            created: Vec::new(),
+        }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", self.prefix, name))
+    }
+
+    /// Creates the output file `name`, which must not already exist unless overwriting is
+    /// allowed, so that runs sharing a directory don't silently replace each other's output
+    pub fn create(&mut self, name: &str) -> Result<std::fs::File> {
+        let path = self.path(name);
+        let file = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| {
+                let mut options = std::fs::OpenOptions::new();
+                options.write(true);
+                if self.overwrite {
+                    options.create(true).truncate(true);
+                } else {
+                    options.create_new(true);
+                }
+                options.open(&path)
+            })
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => anyhow::anyhow!(
+                    "Output file {} already exists; set output.overwrite = true to replace it",
+                    path.display()
+                ),
+                _ => anyhow::anyhow!("Failed to create output file {}: {}", path.display(), e),
+            })?;
+        self.created.push(path);
+        Ok(file)
+    }
+
+    /// The name a stream is written under, with the extension for its compression
+    pub fn stream_name(&self, name: &str) -> String {
+        format!("{name}{}", self.compression.extension())
+    }
+
+    /// Creates a file for output written as the simulation runs, compressed if asked
+    pub fn create_stream(&mut self, name: &str) -> Result<OutputFile> {
+        let file = self.create(&self.stream_name(name))?;
+        OutputFile::new(file, self.compression)
+    }
+
+    pub fn create_csv(&mut self, name: &str) -> Result<CsvWriter> {
+        Ok(CsvWriter::new(self.create_stream(name)?))
+    }
 }
 
 struct Counts {
     total_infections: usize,
     forecasts_rejected: usize,
     daily_incidence: Vec<usize>,
+    offspring: HashMap<PersonId, Offspring>,
+    last_recovery_time: Option<f64>,
+}
+
+// Who each infected person went on to infect
+struct Offspring {
+    // 0 for people infected at the start of the simulation, otherwise one more than their
+    // infector's generation
+    generation: usize,
+    secondary_cases: usize,
 }
 impl Counts {
     fn new(sim_length: f64) -> Self {
@@ -30,11 +132,40 @@ impl Counts {
             total_infections: 0,
             forecasts_rejected: 0,
             daily_incidence: Vec::with_capacity(sim_length.floor() as usize),
+            offspring: HashMap::default(),
+            last_recovery_time: None,
+        }
+    }
+    fn add_recovery(&mut self, status: Status) {
+        if let Some(t) = status.recovery_time() {
+            self.last_recovery_time = Some(self.last_recovery_time.map_or(t, |last| last.max(t)));
         }
     }
     fn add_forecast_rejection(&mut self) {
         self.forecasts_rejected += 1;
     }
+    // Returns the person's generation. An infector whose own infection was never seen
+    // can't be credited, so the person starts a new chain as if they had no infector.
+    fn add_infectious(&mut self, person_id: PersonId, status: Status) -> usize {
+        let infector = status
+            .infected_by()
+            .and_then(|infector| self.offspring.get_mut(&infector));
+        let generation = match infector {
+            Some(infector) => {
+                infector.secondary_cases += 1;
+                infector.generation + 1
+            }
+            None => 0,
+        };
+        self.offspring.insert(
+            person_id,
+            Offspring {
+                generation,
+                secondary_cases: 0,
+            },
+        );
+        generation
+    }
     fn add_infection(&mut self, status: Status) {
         self.total_infections += 1;
         if let Some(infection_time) = status.infection_time() {
@@ -50,103 +181,809 @@ impl Counts {
 // Store writers in a plugin data container
 struct OutputDataContainer {
     counts: Counts,
-    json_writer: BufWriter<std::fs::File>,
-    daily_incidence_writer: ixa::csv::Writer<std::fs::File>,
+    event_writers: EventWriters,
+    incidence_writer: DailyIncidenceWriter,
+    offspring_writer: CsvWriter,
+    transmission_tree: Option<TransmissionTreeWriter>,
+    generation_intervals: Option<GenerationIntervalWriter>,
+    prevalence_writer: CsvWriter,
+    // Kept for the report, as the files may be compressed
+    prevalence: Vec<(f64, [usize; Compartment::ALL.len()])>,
+    offspring_distribution: Vec<usize>,
+    report_file: std::fs::File,
+    files: OutputFiles,
+    manifest_file: std::fs::File,
+    summary_json: std::fs::File,
+    summary_csv: ixa::csv::Writer<std::fs::File>,
+    started: SystemTime,
+    events_written: usize,
+    // Set once the manifest is written, after which there's nothing left to write
+    finished: bool,
 }
 
 impl OutputDataContainer {
-    fn write_daily_incidence(&mut self) {
-        self.daily_incidence_writer
-            .write_record(&["t", "incidence"])
-            .expect("Failed to write header");
-        for (day, incidence) in self.counts.daily_incidence.iter().enumerate() {
-            self.daily_incidence_writer
-                .write_record(&[day.to_string(), incidence.to_string()])
-                .expect("Failed to write daily incidence");
+    // Makes sure everything written so far is on disk
+    fn flush(&mut self) -> Result<()> {
+        self.event_writers.flush()?;
+        self.incidence_writer.flush()?;
+        self.offspring_writer.flush()?;
+        self.prevalence_writer.flush()?;
+        if let Some(tree) = &mut self.transmission_tree {
+            tree.flush()?;
+        }
+        if let Some(intervals) = &mut self.generation_intervals {
+            intervals.flush()?;
         }
+        Ok(())
+    }
+
+    // Ends the streams `write_accumulated_output` leaves open, which compressed files need
+    // to be complete. Each flushes what it has left as it ends.
+    fn finish(&mut self) -> Result<()> {
+        self.event_writers.finish()?;
+        for writer in [&mut self.offspring_writer, &mut self.prevalence_writer] {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Writes how many infectors went on to infect each number of people
+    fn write_offspring_distribution(&mut self, secondary_cases: &[u64]) {
+        let max = secondary_cases.iter().copied().max().unwrap_or(0) as usize;
+        let mut infectors = vec![0usize; max + 1];
+        for n in secondary_cases {
+            infectors[*n as usize] += 1;
+        }
+        for (n, count) in infectors.iter().enumerate() {
+            self.offspring_writer
+                .write_record(&[n.to_string(), count.to_string()])
+                .expect("Failed to write offspring distribution");
+        }
+        self.offspring_distribution = infectors;
     }
 }
 
 define_data_plugin!(OutputPlugin, OutputDataContainer, |context| {
-    let events_file = create_output_file("events.jsonl").unwrap();
-    let events_writer = BufWriter::new(events_file);
+    let started = SystemTime::now();
+    let mut files = OutputFiles::new(context.param_output(), *context.param_seed());
+    // Written last, but created first so a clash with an earlier run stops this one early
+    let manifest_file = files
+        .create(MANIFEST_FILE)
+        .expect("Failed to create manifest");
+
+    let event_writers = EventWriters::new(&mut files, context.param_output_events())
+        .expect("Failed to create event writers");
 
-    let csv_writer =
-        ixa::csv::Writer::from_path(PathBuf::from(OUTPUT_DIR).join("daily_incidence.csv"))
-            .expect("Failed to create incidence writer");
+    let incidence_writer = files
+        .create_csv("daily_incidence.csv")
+        .map(DailyIncidenceWriter::new)
+        .expect("Failed to create incidence writer");
+    // The header is written up front so the file is complete even if no infector recovers
+    let mut offspring_writer = files
+        .create_csv("offspring_distribution.csv")
+        .expect("Failed to create offspring writer");
+    offspring_writer
+        .write_record(["secondary_cases", "infectors"])
+        .expect("Failed to write header");
+
+    // Asking for the tree in any format turns on the edge list it's written alongside
+    let tree_formats = context.param_output_transmission_tree_formats();
+    let transmission_tree = (*context.param_output_transmission_tree() || !tree_formats.is_empty())
+        .then(|| {
+            TransmissionTreeWriter::new(&mut files, tree_formats)
+                .expect("Failed to create transmission tree writers")
+        });
+
+    let generation_intervals = context.param_output_generation_intervals().then(|| {
+        GenerationIntervalWriter::new(&mut files)
+            .expect("Failed to create generation interval writer")
+    });
+
+    let mut prevalence_writer = files
+        .create_csv("prevalence.csv")
+        .expect("Failed to create prevalence writer");
+    let header = std::iter::once("t").chain(Compartment::ALL.iter().map(|c| c.name()));
+    prevalence_writer
+        .write_record(header)
+        .expect("Failed to write header");
+
+    let report_file = files.create("index.html").expect("Failed to create report");
+    let summary_json = files
+        .create("summary.json")
+        .expect("Failed to create summary");
+    let summary_csv = files
+        .create("summary.csv")
+        .map(ixa::csv::Writer::from_writer)
+        .expect("Failed to create summary");
 
     let max_time = context.param_max_time();
     OutputDataContainer {
         counts: Counts::new(*max_time),
-        json_writer: events_writer,
-        daily_incidence_writer: csv_writer,
+        event_writers,
+        incidence_writer,
+        offspring_writer,
+        transmission_tree,
+        generation_intervals,
+        prevalence_writer,
+        prevalence: Vec::new(),
+        offspring_distribution: Vec::new(),
+        report_file,
+        files,
+        manifest_file,
+        summary_json,
+        summary_csv,
+        started,
+        events_written: 0,
+        finished: false,
     }
 });
 
+/// Runs the simulation and finishes its output. If a plan panics, whatever output has
+/// accumulated is still written before the panic carries on.
+pub fn execute(context: &mut Context) {
+    PANIC_HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            PANIC_MESSAGE.with_borrow_mut(|message| *message = Some(info.to_string()));
+            default_hook(info);
+        }));
+    });
+    if let Err(panic) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| context.execute()))
+    {
+        context.finish_output_after_panic();
+        std::panic::resume_unwind(panic);
+    }
+    context.log_stats();
+}
+
 pub trait OutputManagerExt: PluginContext {
     fn capture_output(&mut self) {
         // Send infection events
         self.subscribe_to_event(
             |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+                if event.current.is_recovered() {
+                    context
+                        .get_data_mut(OutputPlugin)
+                        .counts
+                        .add_recovery(event.current);
+                }
                 if !event.current.is_infectious() {
                     return;
                 }
+                // Initial infections count as infected at time 0
+                let infector_infection_time = event.current.infected_by().map(|infector| {
+                    context
+                        .get_person_property(infector, InfectionStatus)
+                        .infection_time()
+                        .unwrap_or(0.0)
+                });
                 let data = context.get_data_mut(OutputPlugin);
+                let generation = data.counts.add_infectious(event.person_id, event.current);
+                if let Some(tree) = &mut data.transmission_tree {
+                    tree.add_infection(
+                        event.current.infected_by(),
+                        event.person_id,
+                        event.current.infection_time(),
+                        generation,
+                    )
+                    .expect("Failed to write transmission tree");
+                }
+                if let (Some(intervals), Some(infector), Some(t), Some(infector_t)) = (
+                    &mut data.generation_intervals,
+                    event.current.infected_by(),
+                    event.current.infection_time(),
+                    infector_infection_time,
+                ) {
+                    intervals
+                        .add_transmission(infector, event.person_id, t, infector_t)
+                        .expect("Failed to write generation interval");
+                }
 
                 if event.current.is_incidence() {
                     data.counts.add_infection(event.current);
+                    let t = event.current.infection_time().unwrap();
+                    let incidence = &context.get_data(OutputPlugin).incidence_writer;
+                    let stratum = incidence.stratum(context, event.person_id);
+                    context
+                        .get_data_mut(OutputPlugin)
+                        .incidence_writer
+                        .add_infection(t, stratum)
+                        .expect("Failed to record incidence");
 
+                    // Written here so it lands in the event files right when it happens, and
+                    // emitted for anything else subscribed, such as a WebSocket client
                     let output = SimulationEvent::Infection {
-                        t: event.current.infection_time().unwrap(),
+                        t,
                         person_id: event.person_id,
                     };
                     context.write_event(output).expect("Failed to write event");
+                    context.emit_event(output);
                 }
             },
         );
 
-        // Send forecast rejected events
-        self.subscribe_to_event(move |context, event: SimulationEvent| {
-            if let SimulationEvent::ForecastRejected { .. } = event {
+        // Write each day's incidence once it's over. Running last means infections at the
+        // start of the next day have already happened, but they're counted on that day.
+        for stratum in self.param_output_incidence_strata().clone() {
+            let stratum_fn: StratumFn = match stratum {
+                IncidenceStratum::Generation => Box::new(|context, person_id| {
+                    let counts = &context.get_data(OutputPlugin).counts;
+                    counts.offspring[&person_id].generation.to_string()
+                }),
+            };
+            self.get_data_mut(OutputPlugin)
+                .incidence_writer
+                .add_stratum(stratum.name(), stratum_fn)
+                .expect("Failed to stratify incidence");
+        }
+        self.add_periodic_plan_with_phase(
+            1.0,
+            |context| {
+                let today = context.get_current_time().floor() as usize;
+                context
+                    .get_data_mut(OutputPlugin)
+                    .incidence_writer
+                    .close_days_before(today)
+                    .expect("Failed to write daily incidence")
+            },
+            ExecutionPhase::Last,
+        );
+
+        // Count people in each compartment periodically. The index keeps each count cheap,
+        // and running last means the counts include everything that happened at that time.
+        self.index_property(InfectionCompartment);
+        let interval = *self.param_output_prevalence_interval();
+        self.add_periodic_plan_with_phase(
+            interval,
+            |context| {
+                context
+                    .write_prevalence()
+                    .expect("Failed to write prevalence")
+            },
+            ExecutionPhase::Last,
+        );
+
+        // Push output to disk regularly, so a run that is killed or crashes still leaves
+        // readable files up to the last flush
+        let interval = *self.param_output_flush_interval();
+        self.add_periodic_plan_with_phase(
+            interval,
+            |context| {
+                context
+                    .get_data_mut(OutputPlugin)
+                    .flush()
+                    .expect("Failed to flush output")
+            },
+            ExecutionPhase::Last,
+        );
+
+        // Write the model's events, counting forecast rejections. Infections were already
+        // written as they happened.
+        self.subscribe_to_event(move |context, event: SimulationEvent| match event {
+            SimulationEvent::Infection { .. } => {}
+            SimulationEvent::ForecastRejected { .. } => {
                 let data = context.get_data_mut(OutputPlugin);
                 data.counts.add_forecast_rejection();
+                context.write_event(event).expect("Failed to write event")
             }
-            context.write_event(event).expect("Failed to write event")
+            _ => context.write_event(event).expect("Failed to write event"),
         });
     }
 
     fn log_stats(&mut self) {
-        self.get_data_mut(OutputPlugin).write_daily_incidence();
-        let data = self.get_data(OutputPlugin);
+        if self.get_data(OutputPlugin).finished {
+            return;
+        }
+        self.write_accumulated_output()
+            .expect("Failed to write output");
+        let mut summary = Summary::default();
 
+        let infection_duration = self.param_transmission_infection_duration();
+        summary.expected_mean_infectious_period = infection_duration.mean();
         log::info!(
-            "Expected mean infectious period: {:.3}",
-            self.param_infection_duration().mean()
-        );
-        log::info!(
-            "Expected mean infection rate: {:.3}",
-            self.param_infection_rate().mean(),
+            "Expected mean infectious period: {:.3} ({})",
+            summary.expected_mean_infectious_period,
+            infection_duration
         );
-        log::info!("Total infections: {}", data.counts.total_infections);
-        let attack_rate =
-            data.counts.total_infections as f64 / self.get_current_population() as f64;
-        log::info!("Attack rate: {:.3}", attack_rate);
+        // The offspring parameterization derives rates from each person's reproduction number
+        if self.param_transmission_offspring().is_none() {
+            let infection_rate = self.param_transmission_infection_rate();
+            summary.expected_mean_infection_rate = Some(infection_rate.mean());
+            log::info!(
+                "Expected mean infection rate: {:.3} ({})",
+                infection_rate.mean(),
+                infection_rate
+            );
+            self.log_rate_duration_correlation();
+        }
+        self.log_offspring(&mut summary);
+        let data = self.get_data(OutputPlugin);
+        summary.mean_generation_interval =
+            data.generation_intervals.as_ref().and_then(|g| g.mean());
+        if let Some(mean) = summary.mean_generation_interval {
+            log::info!("Mean generation interval: {mean:.3}");
+        }
+
+        summary.total_infections = data.counts.total_infections;
+        log::info!("Total infections: {}", summary.total_infections);
+        let population = self.get_current_population() as f64;
+        summary.attack_rate = data.counts.total_infections as f64 / population;
+        log::info!("Attack rate: {:.3}", summary.attack_rate);
         let total_infections = data.counts.total_infections as f64;
         let rejected_forecasts = data.counts.forecasts_rejected as f64;
-        let forecast_efficiency = if total_infections > 0.0 {
+        summary.forecast_efficiency = if total_infections > 0.0 {
             1.0 - (rejected_forecasts / (total_infections + rejected_forecasts))
         } else {
             0.0
         };
-        log::info!("Forecast efficiency: {:.3}", forecast_efficiency);
+        log::info!("Forecast efficiency: {:.3}", summary.forecast_efficiency);
+
+        // The first day with the most new infections
+        if let Some((day, incidence)) = data
+            .counts
+            .daily_incidence
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, incidence)| **incidence)
+            .filter(|(_, incidence)| **incidence > 0)
+        {
+            summary.peak_incidence = *incidence;
+            summary.peak_day = Some(day);
+            log::info!("Peak incidence: {incidence} on day {day}");
+        }
+        let still_infectious =
+            self.query_people_count((InfectionCompartment, Compartment::Infectious));
+        summary.epidemic_duration = if still_infectious == 0 {
+            Some(data.counts.last_recovery_time.unwrap_or(0.0))
+        } else {
+            None
+        };
+        match summary.epidemic_duration {
+            Some(duration) => log::info!("Epidemic duration: {duration:.3}"),
+            None => log::info!("Epidemic ongoing: {still_infectious} people still infectious"),
+        }
+        summary.final_susceptible_fraction =
+            self.query_people_count((InfectionCompartment, Compartment::Susceptible)) as f64
+                / population;
+        log::info!(
+            "Final susceptible fraction: {:.3}",
+            summary.final_susceptible_fraction
+        );
+
+        self.write_summary(&summary)
+            .expect("Failed to write summary");
+        self.write_report(&summary).expect("Failed to write report");
+        self.write_manifest(None).expect("Failed to write manifest");
+    }
+
+    /// Writes everything collected over a run that panicked, without the summary, and a
+    /// manifest recording the panic. Call this when `execute` panics, before unwinding
+    /// any further.
+    fn finish_output_after_panic(&mut self) {
+        if self.get_data(OutputPlugin).finished {
+            return;
+        }
+        let message = PANIC_MESSAGE
+            .with_borrow_mut(Option::take)
+            .unwrap_or_else(|| "panicked".to_string());
+        let result = self
+            .write_accumulated_output()
+            .and_then(|_| self.write_manifest(Some(message)));
+        if let Err(e) = result {
+            log::error!("Failed to write output after panic: {e}");
+        }
+    }
+
+    // Writes the output that's built up over the run rather than streamed
+    fn write_accumulated_output(&mut self) -> Result<()> {
+        self.get_data_mut(OutputPlugin).incidence_writer.finish()?;
+        if let Some(tree) = &mut self.get_data_mut(OutputPlugin).transmission_tree {
+            tree.finish()?;
+        }
+        if let Some(intervals) = &mut self.get_data_mut(OutputPlugin).generation_intervals {
+            intervals.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Compares the rank correlation between the infection rates and durations people
+    /// were actually assigned with the one `rate_duration_correlation` should produce
+    fn log_rate_duration_correlation(&self) {
+        let (rates, durations): (Vec<f64>, Vec<f64>) = self
+            .get_rate_fns(InfectionRate)
+            .filter_map(|rate_fn| {
+                let duration = rate_fn.infection_duration();
+                (duration > 0.0).then(|| (rate_fn.cum_rate(duration) / duration, duration))
+            })
+            .unzip();
+        if rates.len() < 2 {
+            return;
+        }
+        let correlation = *self.param_transmission_rate_duration_correlation();
+        let expected = GaussianCopula::new(correlation)
+            .map(|copula| copula.spearman_rho())
+            .unwrap_or(f64::NAN);
+        log::info!(
+            "Rate/duration rank correlation: {:.3} (expected {:.3} for correlation = {})",
+            rank_correlation(&rates, &durations),
+            expected,
+            correlation
+        );
+    }
+
+    /// Reports the number of people each infector went on to infect against the expected
+    /// R0, overall and by generation, and writes its distribution to CSV. People still
+    /// infectious when the simulation stops could have infected more, so they're left out.
+    fn log_offspring(&mut self, summary: &mut Summary) {
+        let product_of_means = self.param_transmission_infection_rate().mean()
+            * self.param_transmission_infection_duration().mean();
+        let expected_r0 = match self.param_transmission_offspring() {
+            Some(offspring) => offspring.r0,
+            None if *self.param_transmission_rate_duration_correlation() == 0.0 => product_of_means,
+            // With correlated draws, E[rate * duration] isn't the product of the means, so
+            // average the individual reproduction numbers people were actually assigned. If
+            // no one was infected there's nothing to average, and the product is all we have.
+            None => {
+                let (total, n) = self
+                    .get_rate_fns(InfectionRate)
+                    .map(|rate_fn| rate_fn.cum_rate(rate_fn.infection_duration()))
+                    .fold((0.0, 0), |(total, n), r| (total + r, n + 1));
+                if n > 0 {
+                    total / n as f64
+                } else {
+                    product_of_means
+                }
+            }
+        };
+        summary.expected_r0 = expected_r0;
+        match self.param_transmission_offspring() {
+            Some(offspring) => log::info!("Expected R0: {expected_r0:.3} (k = {})", offspring.k),
+            None => log::info!("Expected R0: {expected_r0:.3}"),
+        }
+
+        let mut censored = 0;
+        let mut completed: Vec<(usize, u64)> = Vec::new();
+        for (person_id, offspring) in &self.get_data(OutputPlugin).counts.offspring {
+            if self
+                .get_person_property(*person_id, InfectionStatus)
+                .is_infectious()
+            {
+                censored += 1;
+            } else {
+                completed.push((offspring.generation, offspring.secondary_cases as u64));
+            }
+        }
+        if completed.is_empty() {
+            return;
+        }
+        let secondary_cases: Vec<u64> = completed.iter().map(|(_, n)| *n).collect();
+        self.get_data_mut(OutputPlugin)
+            .write_offspring_distribution(&secondary_cases);
+
+        let n = secondary_cases.len() as f64;
+        let mean = secondary_cases.iter().sum::<u64>() as f64 / n;
+        let variance = secondary_cases
+            .iter()
+            .map(|x| (*x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0).max(1.0);
+        log::info!(
+            "Secondary cases per infector: mean {mean:.3}, variance {variance:.3} ({} infectors, {censored} still infectious excluded)",
+            secondary_cases.len()
+        );
+        summary.secondary_cases_mean = Some(mean);
+        summary.secondary_cases_variance = Some(variance);
+        summary.dispersion_k = NegativeBinomial::fit_k(&secondary_cases);
+        if let Some(k) = summary.dispersion_k {
+            log::info!("Fitted negative binomial dispersion k: {k:.3}");
+        }
+
+        let generations = completed.iter().map(|(g, _)| *g).max().unwrap_or(0) + 1;
+        let mut by_generation = vec![(0u64, 0usize); generations];
+        for (generation, cases) in &completed {
+            by_generation[*generation].0 += cases;
+            by_generation[*generation].1 += 1;
+        }
+        let by_generation = by_generation
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, infectors))| *infectors > 0)
+            .map(|(g, (cases, infectors))| {
+                format!(
+                    "{g}: {:.3} (n = {infectors})",
+                    *cases as f64 / *infectors as f64
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        log::info!("Mean R by generation: {by_generation}");
+    }
+
+    fn write_prevalence(&mut self) -> Result<()> {
+        let t = self.get_current_time();
+        let counts = Compartment::ALL
+            .map(|compartment| self.query_people_count((InfectionCompartment, compartment)));
+        let record = std::iter::once(t.to_string()).chain(counts.iter().map(|n| n.to_string()));
+        let data = self.get_data_mut(OutputPlugin);
+        data.prevalence_writer.write_record(record)?;
+        data.prevalence.push((t, counts));
+        Ok(())
     }
 
     fn write_event(&mut self, event: SimulationEvent) -> Result<()> {
+        let params = self.param_output_events();
+        if !params.includes(&event) {
+            return Ok(());
+        }
+        let fraction = params.sample_fraction(event.event_type());
+        if fraction < 1.0 && !self.sample_bool(EventSampleRng, fraction) {
+            return Ok(());
+        }
         let plugin_data = self.get_data_mut(OutputPlugin);
-        serde_json::to_writer(&mut plugin_data.json_writer, &event)?;
-        writeln!(&mut plugin_data.json_writer)?;
+        plugin_data.event_writers.write(&event)?;
+        plugin_data.events_written += 1;
+        Ok(())
+    }
+
+    fn write_summary(&mut self, summary: &Summary) -> Result<()> {
+        let data = self.get_data_mut(OutputPlugin);
+        serde_json::to_writer_pretty(&mut data.summary_json, summary)?;
+        writeln!(&mut data.summary_json)?;
+        data.summary_csv.serialize(summary)?;
+        data.summary_csv.flush()?;
+        Ok(())
+    }
+
+    /// Writes the HTML report, which `mise run plot` serves
+    fn write_report(&mut self, summary: &Summary) -> Result<()> {
+        let data = self.get_data(OutputPlugin);
+        let report = Report {
+            params: self.params(),
+            summary,
+            daily_incidence: &data.counts.daily_incidence,
+            prevalence: &data.prevalence,
+            offspring_distribution: &data.offspring_distribution,
+        };
+        let mut html = Vec::new();
+        report.write(&mut html)?;
+        self.get_data_mut(OutputPlugin)
+            .report_file
+            .write_all(&html)?;
+        Ok(())
+    }
+
+    /// Writes `manifest.json`, recording the parameters and code version that produced
+    /// this run along with checksums of everything else it wrote, and the error that
+    /// stopped it if any. This should come after all other output.
+    fn write_manifest(&mut self, error: Option<String>) -> Result<()> {
+        let simulated_end_time = self.get_current_time();
+        self.get_data_mut(OutputPlugin).finish()?;
+        let data = self.get_data(OutputPlugin);
+        let manifest_path = data.files.path(MANIFEST_FILE);
+        let outputs: Vec<&PathBuf> = data
+            .files
+            .created
+            .iter()
+            .filter(|path| **path != manifest_path)
+            .collect();
+        let manifest = Manifest::new(
+            self.params(),
+            data.started,
+            simulated_end_time,
+            data.events_written,
+            &outputs,
+            error,
+        )?;
+        let json = serde_json::to_string_pretty(&manifest)?;
+        let data = self.get_data_mut(OutputPlugin);
+        writeln!(&mut data.manifest_file, "{json}")?;
+        data.finished = true;
         Ok(())
     }
 }
 
 impl<C> OutputManagerExt for C where C: PluginContext {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::params::Params;
+    use crate::transmission_tree::TreeFormat;
+
+    // A context writing its output to a fresh directory named `name`, with `size` people
+    fn test_context(name: &str, size: usize) -> (Context, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let mut params = Params::default();
+        params.output.dir = dir.display().to_string();
+        params.output.overwrite = true;
+        let mut context = Context::new();
+        context.set_params(params);
+        context.init_random(1);
+        for _ in 0..size {
+            context.add_person(()).unwrap();
+        }
+        (context, dir)
+    }
+
+    fn infectious(infection_time: f64) -> Status {
+        Status::Infectious(InfectionData {
+            infection_time: Some(infection_time),
+            infected_by: None,
+            recovery_time: None,
+        })
+    }
+
+    #[test]
+    fn test_output_files_are_templated_and_not_overwritten() {
+        let mut params = Params::default().output;
+        let root = std::env::temp_dir().join(format!("output_files_{}", std::process::id()));
+        params.dir = root.join("{scenario}").display().to_string();
+        params.prefix = "seed_{seed}_".to_string();
+        params.scenario = "baseline".to_string();
+
+        let mut files = OutputFiles::new(&params, 7);
+        assert_eq!(
+            files.path("events.jsonl"),
+            root.join("baseline").join("seed_7_events.jsonl")
+        );
+        files.create("events.jsonl").unwrap();
+        let error = files.create("events.jsonl").unwrap_err();
+        assert!(error.to_string().contains("already exists"), "{error}");
+
+        params.overwrite = true;
+        OutputFiles::new(&params, 7).create("events.jsonl").unwrap();
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_prevalence_counts_each_compartment() {
+        let (mut context, dir) = test_context("prevalence", 3);
+        context.capture_output();
+        let people = context.query_people(());
+        let (first, second) = (people[0], people[1]);
+        context.add_plan(0.5, move |context| {
+            context.set_person_property(first, InfectionStatus, infectious(0.5));
+        });
+        context.add_plan(1.5, move |context| {
+            let recovered = infectious(0.5).to_recovered(1.5).unwrap();
+            context.set_person_property(first, InfectionStatus, recovered);
+            context.set_person_property(second, InfectionStatus, infectious(1.5));
+        });
+        context.add_plan(2.5, |context| {
+            let count =
+                |compartment| context.query_people_count((InfectionCompartment, compartment));
+            assert_eq!(count(Compartment::Susceptible), 1);
+            assert_eq!(count(Compartment::Infectious), 1);
+            assert_eq!(count(Compartment::Recovered), 1);
+            context.shutdown();
+        });
+        context.execute();
+
+        let data = context.get_data_mut(OutputPlugin);
+        assert_eq!(
+            data.prevalence,
+            vec![(0.0, [3, 0, 0]), (1.0, [2, 1, 0]), (2.0, [1, 1, 1])]
+        );
+        data.flush().unwrap();
+        let csv = std::fs::read_to_string(dir.join("prevalence.csv")).unwrap();
+        assert_eq!(csv, "t,S,I,R\n0,3,0,0\n1,2,1,0\n2,1,1,1\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    // Runs the whole model for 10 days on 50 people, with output in a fresh directory
+    // named `name`
+    fn run_model(name: &str, configure: impl FnOnce(&mut Params)) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let mut params = Params {
+            max_time: 10.0,
+            ..Params::default()
+        };
+        params.population.size = 50;
+        params.output.dir = dir.display().to_string();
+        params.output.overwrite = true;
+        configure(&mut params);
+        let mut context = crate::model::setup(Some(params)).unwrap();
+        execute(&mut context);
+        dir
+    }
+
+    #[test]
+    fn test_run_writes_complete_output() {
+        let dir = run_model("complete_run", |params| {
+            params.population.p_initial_incidence = 0.1;
+        });
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(manifest["completed"], true);
+        assert!(manifest["error"].is_null());
+        let incidence = std::fs::read_to_string(dir.join("daily_incidence.csv")).unwrap();
+        assert!(incidence.starts_with("t,incidence\n"), "{incidence}");
+        let offspring = std::fs::read_to_string(dir.join("offspring_distribution.csv")).unwrap();
+        assert!(
+            offspring.starts_with("secondary_cases,infectors\n"),
+            "{offspring}"
+        );
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("summary.json")).unwrap())
+                .unwrap();
+
+        // Every infection is written once, and events come out in the order they happened
+        let events: Vec<serde_json::Value> = std::fs::read_to_string(dir.join("events.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let infections = events.iter().filter(|e| e["type"] == "Infection").count();
+        assert_eq!(
+            infections as u64,
+            summary["total_infections"].as_u64().unwrap()
+        );
+        let times: Vec<f64> = events.iter().map(|e| e["t"].as_f64().unwrap()).collect();
+        assert!(times.is_sorted(), "{times:?}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_tree_format_turns_on_the_tree() {
+        let dir = run_model("tree_format", |params| {
+            params.population.p_initial_incidence = 0.1;
+            params.output.transmission_tree = false;
+            params.output.transmission_tree_formats = vec![TreeFormat::Newick];
+        });
+        assert!(dir.join("transmission_tree.csv").exists());
+        assert!(dir.join("transmission_tree.nwk").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_expected_r0_without_infections() {
+        let dir = run_model("no_infections", |params| {
+            params.population.p_initial_incidence = 0.0;
+            params.transmission.rate_duration_correlation = 0.5;
+        });
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("summary.json")).unwrap())
+                .unwrap();
+        // With no one to average over, the product of the mean rate and duration
+        assert_eq!(summary["expected_r0"].as_f64(), Some(0.5 * 3.0));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_panic_is_recorded_in_manifest() {
+        let (mut context, dir) = test_context("panicked_run", 3);
+        context.capture_output();
+        context.add_plan(1.0, |_| panic!("plan failed"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            execute(&mut context);
+        }));
+        assert!(result.is_err());
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(manifest["completed"], false);
+        let error = manifest["error"].as_str().unwrap();
+        assert!(error.contains("plan failed"), "{error}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_infector_starts_a_new_chain() {
+        let mut counts = Counts::new(10.0);
+        let mut context = Context::new();
+        let infector = context.add_person(()).unwrap();
+        let infectee = context.add_person(()).unwrap();
+        let status = Status::Infectious(InfectionData {
+            infection_time: Some(1.0),
+            infected_by: Some(infector),
+            recovery_time: None,
+        });
+        assert_eq!(counts.add_infectious(infectee, status), 0);
+        assert_eq!(counts.offspring[&infectee].secondary_cases, 0);
+        assert!(!counts.offspring.contains_key(&infector));
+    }
+}
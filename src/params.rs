@@ -1,17 +1,55 @@
 use std::sync::LazyLock;
 
-use crate::ixa_plus::{define_parameters, distr::gamma::*};
+use crate::compression::Compression;
+use crate::event_output::{EventOutputLayer, EventOutputParams};
+use crate::incidence::IncidenceStratum;
+use crate::ixa_plus::{
+    define_parameters,
+    distr::any::{AnyDiscreteDistribution, AnyDistribution},
+};
+use crate::transmission_tree::TreeFormat;
 use anyhow::bail;
 use ixa::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An individual reproduction number distribution given by its mean `r0` and dispersion `k`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct OffspringParams {
+    pub r0: f64,
+    pub k: f64,
+}
 
 define_parameters! {
     defaults: "../params/default.toml",
     pub struct Params {
+        /// The maximum run time of the simulation; even if there are still infections
+        /// scheduled to occur, the simulation will stop at this time.
+        max_time: f64 {
+            validate(value) {
+                if *value < 0.0 {
+                    bail!("max_time must be non-negative");
+                }
+            }
+        },
+
+        /// The random seed for the simulation.
+        seed: u64 {
+            validate(value) {
+                if *value == 0 {
+                    bail!("seed must be non-zero");
+                }
+            }
+        },
+    }
+
+    /// Parameters for seeding the initial population
+    [population]
+    pub struct PopulationParams {
         // The number of people in the population
-        population_size: usize {
+        size: usize {
             validate(value) {
                 if *value == 0 {
-                    bail!("population_size must be greater than 0");
+                    bail!("population size must be greater than 0");
                 }
             }
         },
@@ -36,46 +74,355 @@ define_parameters! {
             }
         },
 
-        /// The maximum run time of the simulation; even if there are still infections
-        /// scheduled to occur, the simulation will stop at this time.
-        max_time: f64 {
+        /// The number of cases imported from outside the population each day, drawn afresh
+        /// every day, e.g. `{ type = "poisson", mean = 0.5 }`. Each infects a random
+        /// susceptible person. None imports no cases.
+        imported_cases: Option<AnyDiscreteDistribution> {
+            default: None,
+        },
+    }
+
+    /// Parameters for each infected person's infectiousness
+    [transmission]
+    pub struct TransmissionParams {
+        /// The distribution of infection rates across the population
+        infection_rate: AnyDistribution,
+
+        /// The distribution of infection durations across the population
+        infection_duration: AnyDistribution,
+
+        /// Correlation between each person's infection rate and infection duration, applied
+        /// through a Gaussian copula so both distributions are kept as they are. 0 samples
+        /// them independently.
+        rate_duration_correlation: f64 {
             validate(value) {
-                if *value < 0.0 {
-                    bail!("max_time must be non-negative");
+                if !(-1.0..=1.0).contains(value) {
+                    bail!("rate_duration_correlation must be between -1 and 1");
                 }
             }
         },
 
-        /// The random seed for the simulation.
-        seed: u64 {
+        /// If set, each person's total expected number of infections is drawn from
+        /// Gamma(shape = k, scale = r0 / k) and spread evenly over their infection duration,
+        /// in place of `infection_rate` and `rate_duration_correlation`. Smaller `k` gives a
+        /// more overdispersed offspring distribution.
+        offspring: Option<OffspringParams> {
+            default: None,
             validate(value) {
-                if *value == 0 {
-                    bail!("seed must be non-zero");
+                if let Some(OffspringParams { r0, k }) = value {
+                    if !(*r0 > 0.0 && r0.is_finite()) {
+                        bail!("offspring r0 must be positive and finite");
+                    }
+                    if !(*k > 0.0 && k.is_finite()) {
+                        bail!("offspring k must be positive and finite");
+                    }
                 }
             }
         },
+    }
 
-        /// The distribution of infection rates across the population
-        infection_rate: Gamma,
+    /// Parameters for what the simulation writes to the output directory
+    [output]
+    pub struct OutputParams {
+        /// The directory output files are written to. `{seed}` and `{scenario}` are replaced
+        /// with the run's seed and scenario, e.g. `output/{scenario}/{seed}`.
+        dir: String,
 
-        /// The distribution of infection durations across the population
-        infection_duration: Gamma,
+        /// Prepended to the name of every output file; also accepts `{seed}` and `{scenario}`
+        prefix: String,
+
+        /// Names this run in `dir` and `prefix`
+        scenario: String,
+
+        /// Whether output files from an earlier run may be replaced. If not, the run fails
+        /// when one of its output files already exists.
+        overwrite: bool,
+
+        /// Compresses the CSV and JSON lines output as it's written, adding `.gz` or `.zst`
+        /// to the file names. The manifest and summaries are left as they are.
+        compression: Compression,
+
+        /// Which events are written and how: their types, a sampling fraction per type, a
+        /// time window, the file format, and whether each type gets its own file
+        events: EventOutputParams {
+            layer: EventOutputLayer,
+            validate(value) {
+                value.validate()?;
+            }
+        },
+
+        /// Extra columns splitting `daily_incidence.csv`, e.g. `["generation"]`
+        incidence_strata: Vec<IncidenceStratum>,
+
+        /// How often, in simulated days, to write the number of people in each compartment
+        /// to `prevalence.csv`
+        prevalence_interval: f64 {
+            validate(value) {
+                if !(*value > 0.0 && value.is_finite()) {
+                    bail!("prevalence_interval must be positive and finite");
+                }
+            }
+        },
+
+        /// How often, in simulated days, to flush output to disk. Shorter intervals lose less
+        /// if a run is killed, at some cost in speed and compression.
+        flush_interval: f64 {
+            validate(value) {
+                if !(*value > 0.0 && value.is_finite()) {
+                    bail!("flush_interval must be positive and finite");
+                }
+            }
+        },
+
+        /// Write the generation interval of every transmission to `generation_intervals.csv`,
+        /// and their distribution by day of infection to `generation_intervals_by_day.csv`
+        generation_intervals: bool,
+
+        /// Write who infected whom to `transmission_tree.csv` as the simulation runs
+        transmission_tree: bool,
+
+        /// Formats to also write the transmission tree in: `newick` and/or `graphml`. Any
+        /// format writes `transmission_tree.csv` too, even if `transmission_tree` is off.
+        transmission_tree_formats: Vec<TreeFormat>,
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::event_output::EventFormat;
+    use crate::ixa_plus::{
+        distr::gamma::*,
+        params_file::{ParamsFormat, parse_overrides},
+        params_macro::IxaParameters,
+    };
+    use crate::simulation_event::EventType;
 
     #[test]
     fn test_rate_distributions() {
-        let params: Params = Params::builder()
-            .infection_duration(gamma!(shape = 3.0, rate = 0.5).unwrap())
-            .try_into()
-            .unwrap();
+        let params: Params =
+            Params::builder()
+                .transmission(TransmissionParams::builder().infection_duration(
+                    AnyDistribution::Gamma(gamma!(shape = 3.0, rate = 0.5).unwrap()),
+                ))
+                .try_into()
+                .unwrap();
         let mut rng = rand::rng();
-        params.infection_duration.sample(&mut rng);
-        assert_eq!(params.infection_duration.scale(), 2.0, "scale");
-        assert_eq!(params.infection_duration.mean(), 1.5, "mean");
+        params.transmission.infection_duration.sample(&mut rng);
+        let AnyDistribution::Gamma(infection_duration) = &params.transmission.infection_duration
+        else {
+            panic!("Expected a Gamma distribution");
+        };
+        assert_eq!(infection_duration.scale(), 2.0, "scale");
+        assert_eq!(infection_duration.mean(), 1.5, "mean");
+    }
+
+    #[test]
+    fn test_nested_group_extends_defaults() {
+        let file_params: ParamsBuilder = toml::from_str(
+            r#"
+            [population]
+            size = 50
+            "#,
+        )
+        .unwrap();
+        let params = file_params.extend_from(Params::builder()).build().unwrap();
+        assert_eq!(params.population.size, 50);
+        assert_eq!(params.population.p_initial_incidence, 0.01);
+        assert_eq!(
+            params.transmission.infection_rate.to_string(),
+            "Gamma(shape = 2, rate = 0.25)"
+        );
+    }
+
+    #[test]
+    fn test_nested_group_validation() {
+        let result: Result<Params, _> = Params::builder()
+            .population(PopulationParams::builder().p_initial_incidence(2.0))
+            .try_into();
+        let error = result.unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("population"), "{message}");
+        assert!(message.contains("p_initial_incidence"), "{message}");
+
+        // Errors in groups are wrapped the same way as top-level ones, and only once
+        let result: Result<Params, _> = Params::builder().max_time(-1.0).try_into();
+        for error in [error, result.unwrap_err()] {
+            assert!(error.downcast_ref::<IxaError>().is_some(), "{error}");
+            let message = error.to_string();
+            assert_eq!(message.matches("Validation failed").count(), 1, "{message}");
+        }
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let file_params: ParamsBuilder = ParamsFormat::Yaml
+            .parse(
+                "population:\n  size: 50\ntransmission:\n  infection_rate:\n    type: lognormal\n    mean: 1.0\n    sd: 0.5\n",
+                "params.yaml",
+            )
+            .unwrap();
+        let params = file_params.extend_from(Params::builder()).build().unwrap();
+        assert_eq!(params.population.size, 50);
+        assert_eq!(params.transmission.infection_rate.mean(), 1.0);
+
+        let yaml = params.to_string_as(ParamsFormat::Yaml).unwrap();
+        let reparsed: Params = ParamsFormat::Yaml
+            .parse::<ParamsBuilder, _>(&yaml, "params.yaml")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(reparsed.to_string(), params.to_string());
+    }
+
+    #[test]
+    fn test_parse_error_includes_location() {
+        let error = ParamsFormat::Yaml
+            .parse::<ParamsBuilder, _>("population:\n  size: lots\n", "params.yaml")
+            .unwrap_err();
+        assert!(error.to_string().starts_with("params.yaml:2:"), "{error}");
+    }
+
+    #[test]
+    fn test_extends_layers_files_in_order() {
+        let dir = std::env::temp_dir().join(format!("params_extends_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("scenarios")).unwrap();
+        std::fs::write(dir.join("base.toml"), "seed = 7\n[population]\nsize = 10\n").unwrap();
+        std::fs::write(dir.join("region.yaml"), "population:\n  size: 20\n").unwrap();
+        std::fs::write(
+            dir.join("scenarios/scenario.toml"),
+            "extends = [\"../base.toml\", \"../region.yaml\"]\nmax_time = 5.0\n",
+        )
+        .unwrap();
+
+        let params = Params::try_from_file(dir.join("scenarios/scenario.toml")).unwrap();
+        assert_eq!(params.seed, 7);
+        assert_eq!(params.population.size, 20);
+        assert_eq!(params.max_time, 5.0);
+
+        std::fs::write(
+            dir.join("base.toml"),
+            "extends = \"scenarios/scenario.toml\"\n",
+        )
+        .unwrap();
+        let error = Params::try_from_file(dir.join("scenarios/scenario.toml")).unwrap_err();
+        assert!(error.to_string().contains("cycle"), "{error}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_extends_layers_event_settings() {
+        let dir = std::env::temp_dir().join(format!("params_events_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("base.toml"),
+            "[output.events]\nformat = \"csv\"\nend = 50.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("scenario.toml"),
+            "extends = \"base.toml\"\n[output.events]\ntypes = [\"infection\"]\n",
+        )
+        .unwrap();
+
+        let params = Params::try_from_file(dir.join("scenario.toml")).unwrap();
+        let events = &params.output.events;
+        assert_eq!(events.format, EventFormat::Csv);
+        assert_eq!(events.types, [EventType::Infection]);
+        assert_eq!(events.end, Some(50.0));
+        // Settings no file gives come from the defaults
+        assert_eq!(events.start, 0.0);
+        assert!(!events.split_by_type);
+
+        let overrides = ["output.events.start=10.0".to_string()];
+        let params = parse_overrides::<Params>(&overrides)
+            .unwrap()
+            .extend_from(Params::builder_from_file(dir.join("scenario.toml")).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(params.output.events.start, 10.0);
+        assert_eq!(params.output.events.format, EventFormat::Csv);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_superspreading_mixture_keeps_default_mean() {
+        let params = Params::try_from_file("params/superspreading.toml").unwrap();
+        let infection_rate = &params.transmission.infection_rate;
+        assert!(matches!(infection_rate, AnyDistribution::Mixture(_)));
+        assert!((infection_rate.mean() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_offspring_parameterization() {
+        let file_params: ParamsBuilder = toml::from_str(
+            r#"
+            [transmission.offspring]
+            r0 = 2.5
+            k = 0.1
+            "#,
+        )
+        .unwrap();
+        let params = file_params.extend_from(Params::builder()).build().unwrap();
+        assert_eq!(
+            params.transmission.offspring,
+            Some(OffspringParams { r0: 2.5, k: 0.1 })
+        );
+        assert_eq!(Params::default().transmission.offspring, None);
+
+        let result: Result<Params, _> = Params::builder()
+            .transmission(
+                TransmissionParams::builder().offspring(Some(OffspringParams { r0: 2.5, k: 0.0 })),
+            )
+            .try_into();
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("offspring k"), "{message}");
+    }
+
+    #[test]
+    fn test_imported_cases() {
+        let file_params: ParamsBuilder = toml::from_str(
+            r#"
+            [population.imported_cases]
+            type = "poisson"
+            mean = 2.0
+            "#,
+        )
+        .unwrap();
+        let params = file_params.extend_from(Params::builder()).build().unwrap();
+        let Some(AnyDiscreteDistribution::Poisson(imported_cases)) =
+            &params.population.imported_cases
+        else {
+            panic!("Expected a Poisson distribution");
+        };
+        assert_eq!(imported_cases.mean(), 2.0);
+        assert!(Params::default().population.imported_cases.is_none());
+    }
+
+    #[test]
+    fn test_command_line_overrides() {
+        let args = [
+            "--set",
+            "population.size=50",
+            "--set",
+            "output.dir=runs/{scenario}",
+            "--set",
+            "transmission.offspring = { r0 = 2.0, k = 0.5 }",
+        ]
+        .map(String::from);
+        let params = Params::try_from_args(&args).unwrap().unwrap();
+        assert_eq!(params.population.size, 50);
+        assert_eq!(params.population.p_initial_incidence, 0.01);
+        assert_eq!(params.output.dir, "runs/{scenario}");
+        assert_eq!(
+            params.transmission.offspring,
+            Some(OffspringParams { r0: 2.0, k: 0.5 })
+        );
+
+        assert!(Params::try_from_args(&[]).unwrap().is_none());
+        let bad = ["--set", "population.size"].map(String::from);
+        assert!(Params::try_from_args(&bad).is_err());
     }
 }
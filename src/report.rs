@@ -0,0 +1,319 @@
+use crate::infection_status::Compartment;
+use crate::params::Params;
+use crate::summary::Summary;
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::io::Write;
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 260.0;
+// Room for axis labels around the plot area
+const MARGIN: f64 = 48.0;
+
+/// A one-page HTML report of a run: epidemic curves, the offspring distribution, the summary
+/// and the parameters. Charts are inline SVG and the data is embedded as JSON, so the file
+/// can be opened anywhere without a network connection.
+#[derive(Serialize)]
+pub struct Report<'a> {
+    pub params: &'a Params,
+    pub summary: &'a Summary,
+    /// New infections by day
+    pub daily_incidence: &'a [usize],
+    /// Time, then the number of people in each compartment
+    pub prevalence: &'a [(f64, [usize; Compartment::ALL.len()])],
+    /// The number of infectors with each number of secondary cases
+    pub offspring_distribution: &'a [usize],
+}
+
+struct Series<'a> {
+    name: &'a str,
+    color: &'a str,
+    points: Vec<(f64, f64)>,
+}
+
+impl Report<'_> {
+    pub fn write(&self, mut out: impl Write) -> Result<()> {
+        let mut html = String::new();
+        writeln!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Run report: {}</title>\n<style>{STYLE}</style>\n</head>\n<body>",
+            escape(&self.params.output.scenario)
+        )?;
+        writeln!(
+            html,
+            "<h1>Run report: {} (seed {})</h1>",
+            escape(&self.params.output.scenario),
+            self.params.seed
+        )?;
+
+        let summary = &self.summary;
+        writeln!(html, "<div class=\"headline\">")?;
+        for (label, value) in [
+            ("Total infections", summary.total_infections.to_string()),
+            (
+                "Attack rate",
+                format!("{:.1}%", summary.attack_rate * 100.0),
+            ),
+            (
+                "Peak incidence",
+                match summary.peak_day {
+                    Some(day) => format!("{} on day {day}", summary.peak_incidence),
+                    None => "none".to_string(),
+                },
+            ),
+            (
+                "Final susceptible",
+                format!("{:.1}%", summary.final_susceptible_fraction * 100.0),
+            ),
+        ] {
+            writeln!(
+                html,
+                "<div><span>{label}</span><strong>{}</strong></div>",
+                escape(&value)
+            )?;
+        }
+        writeln!(html, "</div>")?;
+
+        let incidence = Series {
+            name: "Incidence",
+            color: "#d6604d",
+            points: (self.daily_incidence.iter().enumerate())
+                .map(|(day, n)| (day as f64, *n as f64))
+                .collect(),
+        };
+        chart(&mut html, "Daily incidence", "Day", &[incidence], true)?;
+
+        let prevalence: Vec<Series> = (Compartment::ALL.iter().enumerate())
+            .map(|(i, compartment)| Series {
+                name: compartment.name(),
+                color: color(compartment),
+                points: (self.prevalence.iter())
+                    .map(|(t, counts)| (*t, counts[i] as f64))
+                    .collect(),
+            })
+            .collect();
+        chart(&mut html, "Prevalence", "Time", &prevalence, false)?;
+
+        let offspring = Series {
+            name: "Infectors",
+            color: "#8073ac",
+            points: (self.offspring_distribution.iter().enumerate())
+                .map(|(n, infectors)| (n as f64, *infectors as f64))
+                .collect(),
+        };
+        chart(
+            &mut html,
+            "Offspring distribution",
+            "Secondary cases",
+            &[offspring],
+            true,
+        )?;
+
+        writeln!(html, "<h2>Summary</h2>\n<table>")?;
+        if let serde_json::Value::Object(fields) = serde_json::to_value(summary)? {
+            for (name, value) in fields {
+                let value = match value {
+                    serde_json::Value::Null => "–".to_string(),
+                    serde_json::Value::Number(n) => match n.as_f64() {
+                        Some(x) if n.is_f64() => format!("{x:.4}"),
+                        _ => n.to_string(),
+                    },
+                    value => value.to_string(),
+                };
+                writeln!(html, "<tr><th>{name}</th><td>{}</td></tr>", escape(&value))?;
+            }
+        }
+        writeln!(html, "</table>")?;
+
+        writeln!(
+            html,
+            "<h2>Parameters</h2>\n<pre>{}</pre>",
+            escape(&self.params.to_string())
+        )?;
+
+        // For anyone who wants to redraw the charts; `</` can't appear inside a script
+        let data = serde_json::to_string(self)?.replace("</", "<\\/");
+        writeln!(
+            html,
+            "<script type=\"application/json\" id=\"report-data\">{data}</script>\n</body>\n</html>"
+        )?;
+        out.write_all(html.as_bytes())?;
+        Ok(())
+    }
+}
+
+// Draws an SVG chart of one or more series against shared axes, as bars or lines
+fn chart(
+    html: &mut String,
+    title: &str,
+    x_label: &str,
+    series: &[Series],
+    bars: bool,
+) -> Result<()> {
+    writeln!(html, "<h2>{title}</h2>")?;
+    let points = || series.iter().flat_map(|s| s.points.iter());
+    if points().next().is_none() {
+        writeln!(html, "<p>No data</p>")?;
+        return Ok(());
+    }
+    let (x_min, x_max) = points().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (x, _)| {
+        (lo.min(*x), hi.max(*x))
+    });
+    // Bars are centred on their x value, so leave half a bar either side
+    let (x_min, x_max) = if bars {
+        (x_min - 0.5, x_max + 0.5)
+    } else {
+        (x_min, x_max.max(x_min + 1.0))
+    };
+    let y_max = points().map(|(_, y)| *y).fold(1.0, f64::max);
+    let x = |value: f64| MARGIN + (value - x_min) / (x_max - x_min) * (WIDTH - 2.0 * MARGIN);
+    let y = |value: f64| HEIGHT - MARGIN - value / y_max * (HEIGHT - 2.0 * MARGIN);
+
+    writeln!(
+        html,
+        "<svg viewBox=\"0 0 {WIDTH} {HEIGHT}\" role=\"img\" aria-label=\"{title}\">"
+    )?;
+    // Axes with five ticks each
+    writeln!(
+        html,
+        "<path class=\"axis\" d=\"M{MARGIN},{MARGIN} V{} H{}\"/>",
+        HEIGHT - MARGIN,
+        WIDTH - MARGIN
+    )?;
+    for i in 0..=4 {
+        let fraction = i as f64 / 4.0;
+        let tick_y = y_max * fraction;
+        writeln!(
+            html,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            MARGIN - 6.0,
+            y(tick_y) + 4.0,
+            format_tick(tick_y)
+        )?;
+        let tick_x = x_min + (x_max - x_min) * fraction;
+        writeln!(
+            html,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x(tick_x),
+            HEIGHT - MARGIN + 16.0,
+            format_tick(tick_x)
+        )?;
+    }
+    writeln!(
+        html,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{x_label}</text>",
+        WIDTH / 2.0,
+        HEIGHT - 8.0
+    )?;
+
+    for (i, s) in series.iter().enumerate() {
+        if bars {
+            let width = (x(1.0) - x(0.0)) * 0.9;
+            for (px, py) in &s.points {
+                writeln!(
+                    html,
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{px}: {py}</title></rect>",
+                    x(*px) - width / 2.0,
+                    y(*py),
+                    y(0.0) - y(*py),
+                    s.color
+                )?;
+            }
+        } else {
+            let path: Vec<String> = (s.points.iter())
+                .map(|(px, py)| format!("{:.1},{:.1}", x(*px), y(*py)))
+                .collect();
+            writeln!(
+                html,
+                "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>",
+                s.color,
+                path.join(" ")
+            )?;
+        }
+        // Legend, top right
+        let legend_y = MARGIN + 16.0 * i as f64;
+        writeln!(
+            html,
+            "<rect x=\"{}\" y=\"{}\" width=\"10\" height=\"10\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"{}\">{}</text>",
+            WIDTH - MARGIN - 100.0,
+            legend_y - 9.0,
+            s.color,
+            WIDTH - MARGIN - 86.0,
+            legend_y,
+            s.name
+        )?;
+    }
+    writeln!(html, "</svg>")?;
+    Ok(())
+}
+
+fn color(compartment: &Compartment) -> &'static str {
+    match compartment {
+        Compartment::Susceptible => "#4393c3",
+        Compartment::Infectious => "#d6604d",
+        Compartment::Recovered => "#5aae61",
+    }
+}
+
+fn format_tick(value: f64) -> String {
+    if value.fract().abs() < 1e-9 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:760px;margin:2em auto;\
+color:#222}svg{width:100%;height:auto}svg text{font-size:11px;fill:#444}\
+.axis{fill:none;stroke:#888}.headline{display:flex;gap:1em;flex-wrap:wrap}\
+.headline div{border:1px solid #ddd;border-radius:6px;padding:.6em 1em}\
+.headline span{display:block;font-size:.8em;color:#666}table{border-collapse:collapse}\
+th,td{text-align:left;padding:.2em 1em .2em 0;border-bottom:1px solid #eee}\
+pre{background:#f6f6f6;padding:1em;overflow:auto}";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report_is_self_contained() {
+        let mut params = Params::default();
+        params.output.scenario = "<baseline>".to_string();
+        let summary = Summary {
+            total_infections: 3,
+            peak_day: Some(1),
+            peak_incidence: 2,
+            ..Default::default()
+        };
+        let report = Report {
+            params: &params,
+            summary: &summary,
+            daily_incidence: &[1, 2],
+            prevalence: &[(0.0, [9, 1, 0]), (1.0, [7, 2, 1])],
+            offspring_distribution: &[],
+        };
+        let mut html = Vec::new();
+        report.write(&mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.contains("Run report: &lt;baseline&gt;"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert_eq!(html.matches("<polyline").count(), Compartment::ALL.len());
+        for compartment in Compartment::ALL {
+            assert!(html.contains(&format!(">{}</text>", compartment.name())));
+        }
+        assert!(html.contains("Offspring distribution</h2>\n<p>No data</p>"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(html.contains("\"daily_incidence\":[1,2]"));
+    }
+}
@@ -1,19 +1,118 @@
+use anyhow::Result;
 use ixa::{IxaEvent, PersonId};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize)]
-#[serde(tag = "type")]
-pub enum SimulationEvent {
-    Infection {
+/// The type of a column in the columnar event output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Float64,
+    UInt64,
+}
+
+/// One value of an event, as written to a column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnValue {
+    Float64(f64),
+    UInt64(u64),
+}
+
+impl std::fmt::Display for ColumnValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ColumnValue::Float64(x) => write!(f, "{x}"),
+            ColumnValue::UInt64(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+/// A type that can be a field of a `SimulationEvent`
+pub trait EventColumn {
+    const TYPE: ColumnType;
+    fn value(&self) -> Result<ColumnValue>;
+}
+
+impl EventColumn for f64 {
+    const TYPE: ColumnType = ColumnType::Float64;
+    fn value(&self) -> Result<ColumnValue> {
+        Ok(ColumnValue::Float64(*self))
+    }
+}
+
+impl EventColumn for PersonId {
+    const TYPE: ColumnType = ColumnType::UInt64;
+    fn value(&self) -> Result<ColumnValue> {
+        // ixa doesn't expose the index behind a PersonId, only its display
+        let index = self.to_string();
+        match index.parse() {
+            Ok(index) => Ok(ColumnValue::UInt64(index)),
+            Err(_) => anyhow::bail!("Person {index} can't be written as an integer column"),
+        }
+    }
+}
+
+// Declares `SimulationEvent` together with `EventType` and the columns of each variant, so
+// the columnar output can't drift from the enum. Every variant needs a time `t`.
+macro_rules! define_simulation_events {
+    ($($variant:ident => $name:literal { $($field:ident: $ty:ty),* $(,)? }),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, Serialize)]
+        #[serde(tag = "type")]
+        pub enum SimulationEvent {
+            $($variant { $($field: $ty),* },)*
+        }
+
+        /// The kinds of `SimulationEvent`, for choosing which are written
+        #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum EventType {
+            $(#[serde(rename = $name)] $variant,)*
+        }
+
+        impl EventType {
+            pub const ALL: &[EventType] = &[$(EventType::$variant),*];
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(EventType::$variant => $name,)*
+                }
+            }
+            /// The name and type of each field of this type's events, in order
+            pub fn columns(&self) -> &'static [(&'static str, ColumnType)] {
+                match self {
+                    $(EventType::$variant => &[$((stringify!($field), <$ty as EventColumn>::TYPE)),*],)*
+                }
+            }
+        }
+
+        impl SimulationEvent {
+            pub fn time(&self) -> f64 {
+                match *self {
+                    $(SimulationEvent::$variant { t, .. } => t,)*
+                }
+            }
+            pub fn event_type(&self) -> EventType {
+                match self {
+                    $(SimulationEvent::$variant { .. } => EventType::$variant,)*
+                }
+            }
+            /// The event's values, in the order of `EventType::columns`
+            pub fn values(&self) -> Result<Vec<ColumnValue>> {
+                match self {
+                    $(SimulationEvent::$variant { $($field),* } => Ok(vec![$($field.value()?),*]),)*
+                }
+            }
+        }
+    };
+}
+
+define_simulation_events! {
+    Infection => "infection" {
         t: f64,
         person_id: PersonId,
     },
-    Contact {
+    Contact => "contact" {
         t: f64,
         person_id: PersonId,
         contact_id: PersonId,
     },
-    ForecastRejected {
+    ForecastRejected => "forecast_rejected" {
         t: f64,
         person_id: PersonId,
     },
@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+/// The headline numbers from a run, written to `summary.json` and `summary.csv` as well as
+/// the log. Values that don't apply to a run, e.g. a peak with no infections, are left out.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Summary {
+    pub total_infections: usize,
+    pub attack_rate: f64,
+    /// The proportion of forecast infection attempts that weren't rejected
+    pub forecast_efficiency: f64,
+    /// The most new infections on a single day, and the first day it happened
+    pub peak_incidence: usize,
+    pub peak_day: Option<usize>,
+    /// When the last infectious person recovered; missing if people were still infectious
+    /// when the simulation stopped
+    pub epidemic_duration: Option<f64>,
+    pub final_susceptible_fraction: f64,
+    pub expected_mean_infectious_period: f64,
+    /// Missing when infectiousness comes from the offspring parameterization
+    pub expected_mean_infection_rate: Option<f64>,
+    pub expected_r0: f64,
+    /// Secondary cases of infectors who had recovered by the end of the simulation
+    pub secondary_cases_mean: Option<f64>,
+    pub secondary_cases_variance: Option<f64>,
+    pub dispersion_k: Option<f64>,
+    pub mean_generation_interval: Option<f64>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary_csv_has_one_row() {
+        let summary = Summary {
+            total_infections: 10,
+            peak_day: Some(3),
+            ..Default::default()
+        };
+        let mut writer = ixa::csv::Writer::from_writer(Vec::new());
+        writer.serialize(&summary).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("total_infections,attack_rate,"));
+        assert!(lines[1].starts_with("10,0.0,0.0,0,3,,"), "{}", lines[1]);
+    }
+}
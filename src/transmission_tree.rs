@@ -0,0 +1,236 @@
+use crate::compression::{self, CsvWriter, OutputFile};
+use crate::output_manager::OutputFiles;
+use anyhow::Result;
+use ixa::{HashMap, PersonId};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const EDGE_LIST_FILE: &str = "transmission_tree.csv";
+const NEWICK_FILE: &str = "transmission_tree.nwk";
+const GRAPHML_FILE: &str = "transmission_tree.graphml";
+
+/// Formats the transmission tree can be written in alongside the edge list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeFormat {
+    Newick,
+    GraphMl,
+}
+
+/// Streams who-infected-whom to disk as infections happen. Every infection is a row of
+/// the edge list, and people with no infector (initial infections) are the roots of
+/// separate trees. Newick nests each subtree inside its infector, so it is built from
+/// the edge list once the simulation is over, holding the whole tree in memory.
+pub struct TransmissionTreeWriter {
+    edge_list: PathBuf,
+    edges: CsvWriter,
+    graphml: Option<OutputFile>,
+    newick: Option<File>,
+}
+
+// A row of the edge list as it's read back to build the Newick trees
+#[derive(Deserialize)]
+struct Edge {
+    infector: Option<usize>,
+    infectee: usize,
+    time: Option<f64>,
+}
+
+impl TransmissionTreeWriter {
+    pub fn new(files: &mut OutputFiles, formats: &[TreeFormat]) -> Result<Self> {
+        let mut edges = files.create_csv(EDGE_LIST_FILE)?;
+        edges.write_record(["infector", "infectee", "time", "generation"])?;
+
+        let graphml = if formats.contains(&TreeFormat::GraphMl) {
+            let mut writer = files.create_stream(GRAPHML_FILE)?;
+            writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+            writeln!(
+                writer,
+                r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+            )?;
+            writeln!(
+                writer,
+                r#"  <key id="time" for="node" attr.name="infection_time" attr.type="double"/>"#
+            )?;
+            writeln!(
+                writer,
+                r#"  <key id="generation" for="node" attr.name="generation" attr.type="int"/>"#
+            )?;
+            writeln!(
+                writer,
+                r#"  <graph id="transmission_tree" edgedefault="directed">"#
+            )?;
+            Some(writer)
+        } else {
+            None
+        };
+
+        // The Newick file is only written at the end, but create it now so a clash with
+        // existing output is caught before the simulation runs
+        let newick = if formats.contains(&TreeFormat::Newick) {
+            Some(files.create(NEWICK_FILE)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            edge_list: files.path(&files.stream_name(EDGE_LIST_FILE)),
+            edges,
+            graphml,
+            newick,
+        })
+    }
+
+    /// Records that `infectee` was infected by `infector` at `time`; initial infections
+    /// have neither
+    pub fn add_infection(
+        &mut self,
+        infector: Option<PersonId>,
+        infectee: PersonId,
+        time: Option<f64>,
+        generation: usize,
+    ) -> Result<()> {
+        let time = time.map(|t| t.to_string()).unwrap_or_default();
+        self.edges.write_record([
+            infector.map(|p| p.to_string()).unwrap_or_default(),
+            infectee.to_string(),
+            time.clone(),
+            generation.to_string(),
+        ])?;
+
+        if let Some(writer) = &mut self.graphml {
+            // The infector's node was written when they were infected, so the edge can
+            // follow straight after the infectee's
+            write!(writer, r#"    <node id="{infectee}">"#)?;
+            if !time.is_empty() {
+                write!(writer, r#"<data key="time">{time}</data>"#)?;
+            }
+            writeln!(
+                writer,
+                r#"<data key="generation">{generation}</data></node>"#
+            )?;
+            if let Some(infector) = infector {
+                writeln!(
+                    writer,
+                    r#"    <edge source="{infector}" target="{infectee}"/>"#
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.edges.flush()?;
+        if let Some(writer) = &mut self.graphml {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Closes out the streamed files and writes the Newick trees, if requested
+    pub fn finish(&mut self) -> Result<()> {
+        self.edges.flush()?;
+        self.edges.finish()?;
+        if let Some(writer) = &mut self.graphml {
+            writeln!(writer, "  </graph>\n</graphml>")?;
+            writer.finish()?;
+        }
+        if let Some(file) = self.newick.take() {
+            write_newick(&self.edge_list, file)?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts an edge list, compressed or not, into Newick, one tree per line for each root. Branch lengths are
+/// the time between a person's infection and their infector's; initial infections count
+/// as infected at time 0.
+pub fn write_newick(edge_list: &Path, output: impl Write) -> Result<()> {
+    let mut roots = Vec::new();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::default();
+    let mut times: HashMap<usize, f64> = HashMap::default();
+    for edge in ixa::csv::Reader::from_reader(compression::open(edge_list)?).deserialize() {
+        let edge: Edge = edge?;
+        times.insert(edge.infectee, edge.time.unwrap_or(0.0));
+        match edge.infector {
+            Some(infector) => children.entry(infector).or_default().push(edge.infectee),
+            None => roots.push(edge.infectee),
+        }
+    }
+
+    // Each person is visited with their infector, if any, to give their branch length
+    enum Step {
+        Visit(usize, Option<usize>),
+        Close(usize, Option<usize>),
+        Comma,
+    }
+    // Transmission chains can be far longer than the call stack allows, so walk each
+    // tree with an explicit stack rather than recursing
+    let mut writer = BufWriter::new(output);
+    for root in roots {
+        let mut stack = vec![Step::Visit(root, None)];
+        while let Some(step) = stack.pop() {
+            let (person, infector) = match step {
+                Step::Comma => {
+                    write!(writer, ",")?;
+                    continue;
+                }
+                Step::Visit(person, infector) => match children.get(&person) {
+                    Some(infectees) => {
+                        write!(writer, "(")?;
+                        stack.push(Step::Close(person, infector));
+                        for (i, infectee) in infectees.iter().enumerate().rev() {
+                            stack.push(Step::Visit(*infectee, Some(person)));
+                            if i > 0 {
+                                stack.push(Step::Comma);
+                            }
+                        }
+                        continue;
+                    }
+                    None => (person, infector),
+                },
+                Step::Close(person, infector) => {
+                    write!(writer, ")")?;
+                    (person, infector)
+                }
+            };
+            write!(writer, "{person}")?;
+            if let Some(infector) = infector {
+                write!(writer, ":{}", times[&person] - times[&infector])?;
+            }
+        }
+        writeln!(writer, ";")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_newick_has_one_tree_per_root() {
+        let dir = std::env::temp_dir().join(format!("transmission_tree_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(EDGE_LIST_FILE),
+            "infector,infectee,time,generation\n\
+             ,1,,0\n\
+             ,5,,0\n\
+             1,2,1.5,1\n\
+             1,3,2,1\n\
+             3,4,3,2\n",
+        )
+        .unwrap();
+        let mut newick = Vec::new();
+        write_newick(&dir.join(EDGE_LIST_FILE), &mut newick).unwrap();
+        assert_eq!(
+            String::from_utf8(newick).unwrap(),
+            "(2:1.5,(4:1)3:2)1;\n5;\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
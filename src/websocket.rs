@@ -0,0 +1,434 @@
+use crate::infection_status::{Compartment, InfectionCompartment};
+use crate::ixa_plus::params_file::parse_overrides;
+use crate::ixa_plus::params_macro::IxaParametersBuilder;
+use crate::params::{ParametersExt, Params};
+use crate::simulation_event::SimulationEvent;
+use anyhow::Result;
+use ixa::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::net::{TcpListener, TcpStream};
+
+/// How the server talks to its client, so sessions can be run over channels in tests
+pub trait Transport {
+    fn send(&mut self, message: String) -> Result<()>;
+    /// The next message from the client, waiting for one if `wait` is set. Returns None if
+    /// there's nothing waiting, and an error once the client has gone.
+    fn receive(&mut self, wait: bool) -> Result<Option<String>>;
+}
+
+pub struct WebSocketTransport(tungstenite::WebSocket<TcpStream>);
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, message: String) -> Result<()> {
+        self.0.send(tungstenite::Message::text(message))?;
+        Ok(())
+    }
+
+    fn receive(&mut self, wait: bool) -> Result<Option<String>> {
+        self.0.get_ref().set_nonblocking(!wait)?;
+        let result = loop {
+            match self.0.read() {
+                Ok(tungstenite::Message::Text(text)) => break Ok(Some(text.to_string())),
+                Ok(tungstenite::Message::Close(_)) => break Err(anyhow::anyhow!("Client closed")),
+                // Pings are answered by tungstenite; nothing else means anything to us
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    break Ok(None);
+                }
+                Err(e) => break Err(e.into()),
+            }
+        };
+        self.0.get_ref().set_nonblocking(false)?;
+        result
+    }
+}
+
+/// Commands from the client, e.g. `{"command": "set", "overrides": ["population.size=5000"]}`
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Parameter overrides in the same form as `--set`, only before the simulation starts
+    Set {
+        overrides: Vec<String>,
+    },
+    Start,
+    Pause,
+    Resume,
+    /// Runs until the next counts are sent, then pauses. Also starts the simulation.
+    Step,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    Paused,
+    Finished,
+}
+
+/// Messages to the client, tagged with `type`
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage<'a> {
+    /// The parameters the simulation will run with, sent on connecting and after each `set`
+    Params {
+        params: &'a Params,
+    },
+    Event {
+        event: SimulationEvent,
+    },
+    Counts {
+        t: f64,
+        susceptible: usize,
+        infectious: usize,
+        recovered: usize,
+    },
+    State {
+        state: RunState,
+        t: f64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+fn send(transport: &mut dyn Transport, message: &ServerMessage) -> Result<()> {
+    transport.send(serde_json::to_string(message)?)
+}
+
+/// Reads the `--websocket` flag, with an optional address after it
+pub fn address_from_args(args: &[String]) -> Option<String> {
+    let position = args.iter().position(|arg| arg == "--websocket")?;
+    let address = args
+        .get(position + 1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or("127.0.0.1:9001", |address| address.as_str());
+    Some(address.to_string())
+}
+
+/// Waits for a client at `address` and runs one simulation for it
+pub fn serve(address: &str, params: Params) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    log::info!("Waiting for a WebSocket client on ws://{address}");
+    let (stream, peer) = listener.accept()?;
+    log::info!("Client connected from {peer}");
+    let websocket = tungstenite::accept(stream).map_err(|e| anyhow::anyhow!("{e}"))?;
+    run_session(Box::new(WebSocketTransport(websocket)), params)
+}
+
+/// Takes parameter updates until the client starts the simulation, then runs it with
+/// events and counts streamed to the client
+pub fn run_session(mut transport: Box<dyn Transport>, params: Params) -> Result<()> {
+    let Some((params, stepping)) = wait_for_start(&mut *transport, params)? else {
+        log::info!("Client left before starting the simulation");
+        return Ok(());
+    };
+    let mut context = crate::model::setup_with(Some(params), |context| {
+        context.stream_to(transport, stepping);
+    })?;
+    crate::output_manager::execute(&mut context);
+    let t = context.get_current_time();
+    context.send_message(&ServerMessage::State {
+        state: RunState::Finished,
+        t,
+    });
+    Ok(())
+}
+
+/// Applies `set` commands to `params` until a `start` or `step`. Returns the parameters
+/// and whether to pause after the first step, or None if the client leaves.
+pub fn wait_for_start(
+    transport: &mut dyn Transport,
+    mut params: Params,
+) -> Result<Option<(Params, bool)>> {
+    send(transport, &ServerMessage::Params { params: &params })?;
+    loop {
+        let Ok(Some(message)) = transport.receive(true) else {
+            return Ok(None);
+        };
+        let updated = match serde_json::from_str::<Command>(&message) {
+            Ok(Command::Start) => return Ok(Some((params, false))),
+            Ok(Command::Step) => return Ok(Some((params, true))),
+            Ok(Command::Set { overrides }) => parse_overrides::<Params>(&overrides)
+                .and_then(|builder| builder.extend_from(params.clone().into()).build()),
+            Ok(command) => Err(anyhow::anyhow!(
+                "{command:?} only applies once the simulation has started"
+            )),
+            Err(e) => Err(anyhow::anyhow!("Unrecognized command {message}: {e}")),
+        };
+        match updated {
+            Ok(updated) => {
+                params = updated;
+                send(transport, &ServerMessage::Params { params: &params })?;
+            }
+            Err(e) => send(
+                transport,
+                &ServerMessage::Error {
+                    message: e.to_string(),
+                },
+            )?,
+        }
+    }
+}
+
+struct StreamState {
+    transport: Option<Box<dyn Transport>>,
+    paused: bool,
+    // Pause at the next counts, after a step
+    stepping: bool,
+}
+
+define_data_plugin!(StreamPlugin, StreamState, |_context| StreamState {
+    transport: None,
+    paused: false,
+    stepping: false,
+});
+
+pub trait StreamExt: PluginContext {
+    /// Sends every `SimulationEvent` to the client, and counts of people in each
+    /// compartment every `prevalence_interval`, when the client's commands are also read.
+    /// The simulation blocks while paused.
+    fn stream_to(&mut self, transport: Box<dyn Transport>, stepping: bool) {
+        let state = self.get_data_mut(StreamPlugin);
+        state.transport = Some(transport);
+        state.stepping = stepping;
+
+        self.subscribe_to_event(|context, event: SimulationEvent| {
+            context.send_message(&ServerMessage::Event { event });
+        });
+        self.index_property(InfectionCompartment);
+        let interval = *self.param_output_prevalence_interval();
+        self.add_periodic_plan_with_phase(
+            interval,
+            |context| {
+                let count =
+                    |compartment| context.query_people_count((InfectionCompartment, compartment));
+                let counts = ServerMessage::Counts {
+                    t: context.get_current_time(),
+                    susceptible: count(Compartment::Susceptible),
+                    infectious: count(Compartment::Infectious),
+                    recovered: count(Compartment::Recovered),
+                };
+                context.send_message(&counts);
+                context.handle_commands();
+            },
+            ExecutionPhase::Last,
+        );
+    }
+
+    /// Sends a message to the client. A client that has gone is dropped, and the
+    /// simulation carries on without it.
+    fn send_message(&mut self, message: &ServerMessage) {
+        let state = self.get_data_mut(StreamPlugin);
+        if let Some(transport) = &mut state.transport
+            && let Err(e) = send(&mut **transport, message)
+        {
+            log::warn!("Lost the WebSocket client: {e}");
+            state.transport = None;
+            state.paused = false;
+        }
+    }
+
+    /// Reads the client's commands, waiting for them while paused
+    fn handle_commands(&mut self) {
+        let state = self.get_data_mut(StreamPlugin);
+        if state.stepping {
+            state.stepping = false;
+            state.paused = true;
+        }
+        let mut announced = None;
+        loop {
+            let state = self.get_data_mut(StreamPlugin);
+            let paused = state.paused;
+            if announced != Some(paused) {
+                let state = if paused {
+                    RunState::Paused
+                } else {
+                    RunState::Running
+                };
+                let t = self.get_current_time();
+                self.send_message(&ServerMessage::State { state, t });
+                announced = Some(paused);
+            }
+
+            let state = self.get_data_mut(StreamPlugin);
+            let Some(transport) = &mut state.transport else {
+                return;
+            };
+            let message = match transport.receive(state.paused) {
+                Ok(Some(message)) => message,
+                Ok(None) => return,
+                Err(e) => {
+                    log::warn!("Lost the WebSocket client: {e}");
+                    state.transport = None;
+                    state.paused = false;
+                    return;
+                }
+            };
+            match serde_json::from_str::<Command>(&message) {
+                Ok(Command::Pause) => state.paused = true,
+                Ok(Command::Resume) => state.paused = false,
+                Ok(Command::Step) => {
+                    state.paused = false;
+                    state.stepping = true;
+                    return;
+                }
+                Ok(command) => self.send_message(&ServerMessage::Error {
+                    message: format!("{command:?} only applies before the simulation starts"),
+                }),
+                Err(e) => self.send_message(&ServerMessage::Error {
+                    message: format!("Unrecognized command {message}: {e}"),
+                }),
+            }
+        }
+    }
+}
+
+impl<C> StreamExt for C where C: PluginContext {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ixa_plus::distr::{any::AnyDiscreteDistribution, categorical::Categorical};
+    use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+    use std::time::Duration;
+
+    // A transport over channels, standing in for a client in the same process
+    struct ChannelTransport {
+        incoming: Receiver<String>,
+        outgoing: Sender<String>,
+    }
+
+    impl Transport for ChannelTransport {
+        fn send(&mut self, message: String) -> Result<()> {
+            self.outgoing.send(message)?;
+            Ok(())
+        }
+
+        fn receive(&mut self, wait: bool) -> Result<Option<String>> {
+            if wait {
+                return Ok(Some(self.incoming.recv()?));
+            }
+            match self.incoming.try_recv() {
+                Ok(message) => Ok(Some(message)),
+                Err(TryRecvError::Empty) => Ok(None),
+                Err(TryRecvError::Disconnected) => anyhow::bail!("Client closed"),
+            }
+        }
+    }
+
+    fn client() -> (Sender<String>, Receiver<String>, ChannelTransport) {
+        let (to_server, incoming) = channel();
+        let (outgoing, from_server) = channel();
+        (
+            to_server,
+            from_server,
+            ChannelTransport { incoming, outgoing },
+        )
+    }
+
+    #[test]
+    fn test_parameter_updates_before_start() {
+        let (to_server, from_server, mut transport) = client();
+        for command in [
+            r#"{"command": "set", "overrides": ["population.size=50", "max_time=5"]}"#,
+            r#"{"command": "pause"}"#,
+            r#"{"command": "set", "overrides": ["population.size=-1"]}"#,
+            r#"{"command": "step"}"#,
+        ] {
+            to_server.send(command.to_string()).unwrap();
+        }
+        let (params, stepping) = wait_for_start(&mut transport, Params::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(params.population.size, 50);
+        assert_eq!(params.max_time, 5.0);
+        assert!(stepping);
+
+        let types: Vec<String> = from_server
+            .try_iter()
+            .map(|message| {
+                let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+                message["type"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(types, ["params", "params", "error", "error"]);
+
+        // A client that leaves before starting ends the session
+        let (to_server, _from_server, mut transport) = client();
+        drop(to_server);
+        assert!(
+            wait_for_start(&mut transport, Params::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_session_streams_a_run() {
+        let dir = std::env::temp_dir().join(format!("websocket_run_{}", std::process::id()));
+        let mut params = Params {
+            max_time: 5.0,
+            ..Params::default()
+        };
+        params.population.size = 50;
+        // An imported case a day makes sure there are infections to stream
+        params.population.imported_cases = Some(AnyDiscreteDistribution::Categorical(
+            Categorical::from_weights(vec![1], vec![1.0]).unwrap(),
+        ));
+        params.output.dir = dir.display().to_string();
+        params.output.overwrite = true;
+
+        let (to_server, from_server, transport) = client();
+        let server = std::thread::spawn(move || run_session(Box::new(transport), params));
+        let mut messages = Vec::new();
+        // Reads messages up to and including the next change of state
+        let mut until_state = |state: &str| loop {
+            let message = from_server.recv_timeout(Duration::from_secs(10)).unwrap();
+            let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+            messages.push(message.clone());
+            if message["type"] == "state" && message["state"] != "running" {
+                assert_eq!(message["state"], state, "{message}");
+                return message["t"].as_f64().unwrap();
+            }
+        };
+
+        // Stepping pauses at each set of counts
+        to_server
+            .send(r#"{"command": "step"}"#.to_string())
+            .unwrap();
+        assert_eq!(until_state("paused"), 0.0);
+        to_server
+            .send(r#"{"command": "step"}"#.to_string())
+            .unwrap();
+        assert_eq!(until_state("paused"), 1.0);
+        to_server
+            .send(r#"{"command": "resume"}"#.to_string())
+            .unwrap();
+        assert_eq!(until_state("finished"), 5.0);
+        server.join().unwrap().unwrap();
+
+        let of_type = |kind: &str| {
+            messages
+                .iter()
+                .filter(|message| message["type"] == kind)
+                .collect::<Vec<_>>()
+        };
+        let counts = of_type("counts");
+        let times: Vec<f64> = counts.iter().map(|c| c["t"].as_f64().unwrap()).collect();
+        assert_eq!(times, [0.0, 1.0, 2.0, 3.0, 4.0]);
+        for c in counts {
+            let total = ["susceptible", "infectious", "recovered"]
+                .map(|compartment| c[compartment].as_u64().unwrap())
+                .iter()
+                .sum::<u64>();
+            assert_eq!(total, 50);
+        }
+        let infections = of_type("event")
+            .into_iter()
+            .filter(|message| message["event"]["type"] == "Infection")
+            .count();
+        assert!(infections >= 5, "{infections} infections streamed");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}